@@ -22,7 +22,8 @@ impl InteractionHandler for DemoHandler {
                     .text(&format!("Hello <@{}>!", ac.user_id))
                     .button("the_button", "the button")
                     .button("modal", "input some text")
-                    .button("spawn", "spawn new message"),
+                    .button("spawn", "spawn new message")
+                    .invoker_only(),
             ),
 
             _ => panic!(),
@@ -34,12 +35,8 @@ impl InteractionHandler for DemoHandler {
             "the_button" => {
                 let n = get_button_clicks(&mc.source.text).unwrap_or(0);
                 Response::Message(
-                    Message::new()
-                        .text(&format!("You've clicked the button {} times.", n + 1))
-                        .button("the_button", "the button")
-                        .button("modal", "input some text")
-                        .button("spawn", "spawn new message")
-                        .edit(),
+                    Message::from_source(&mc.source)
+                        .text(&format!("You've clicked the button {} times.", n + 1)),
                 )
             },
 
@@ -66,19 +63,14 @@ impl InteractionHandler for DemoHandler {
             "my_modal" => {
                 let v1 = ms.values.get("v1").unwrap();
                 let v2 = ms.values.get("v2").unwrap();
+                // This modal is only opened from a button, so it always has a source message.
+                let source = ms.source.as_ref().unwrap();
                 let text = format!(
                     "{}\nYou entered the values `{}` and `{}`.",
-                    ms.source.text, v1, v2
+                    source.text, v1, v2
                 );
 
-                Response::Message(
-                    Message::new()
-                        .text(&text)
-                        .button("the_button", "the button")
-                        .button("modal", "input some text")
-                        .button("spawn", "spawn new message")
-                        .edit(),
-                )
+                Response::Message(Message::from_source(source).text(&text))
             }
 
             _ => panic!(),
@@ -1,13 +1,69 @@
+use super::multipart;
+use super::user_types::InteractionToken;
+use super::watchdog::{handle_with_deadline, Outcome};
+use super::webhook::{HttpTransport, ReqwestTransport, WebhookClient};
 use crate::InteractionHandler;
 use ed25519_dalek::{Signature, Verifier, VerifyingKey, PUBLIC_KEY_LENGTH};
 use lambda_http::http::{HeaderMap, StatusCode};
 use lambda_http::{service_fn, Body, Error, Request, Response};
-use serde_json::json;
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Settings for the Lambda runtime, for use with `run_with_config`.
+pub struct Config {
+    app_pk: String,
+    deadline: Option<Duration>,
+    transport: Arc<dyn HttpTransport>,
+}
+
+/// Convenience methods for building runtime settings.
+impl Config {
+    /// Creates new settings with the given application public key. By default, a handler that
+    /// takes longer than 2 seconds has its response deferred.
+    pub fn new(app_pk: &str) -> Self {
+        Config {
+            app_pk: app_pk.to_string(),
+            deadline: Some(Duration::from_secs(2)),
+            transport: Arc::new(ReqwestTransport::default()),
+        }
+    }
+
+    /// Sets how long a handler may take before the runtime defers the response on its behalf.
+    /// Once deferred, the handler's eventual response is delivered as an edit of the original
+    /// response. Discord allows 3 seconds for the initial response, so the deadline should leave
+    /// some room for sending the deferral.
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Disables the deadline, so that a slow handler fails its interaction rather than having its
+    /// response deferred.
+    pub fn no_deadline(mut self) -> Self {
+        self.deadline = None;
+        self
+    }
+
+    /// Sets the HTTP transport used to defer responses and deliver them through the webhook.
+    pub fn transport(mut self, transport: impl HttpTransport + 'static) -> Self {
+        self.transport = Arc::new(transport);
+        self
+    }
+}
 
 /// Sets up an interaction handler on AWS Lambda to receive and process incoming Discord interactions. This function takes in an application public key, which should match the one provided with your Discord bot.
 pub async fn run<T>(app_pk: &str) -> Result<(), Error>
 where
-    T: InteractionHandler + Sync,
+    T: InteractionHandler + Sync + 'static,
+{
+    run_with_config::<T>(Config::new(app_pk)).await
+}
+
+/// Sets up an interaction handler on AWS Lambda like `run`, using the given settings.
+pub async fn run_with_config<T>(config: Config) -> Result<(), Error>
+where
+    T: InteractionHandler + Sync + 'static,
 {
     tracing_subscriber::fmt()
         .with_max_level(tracing::Level::INFO)
@@ -17,27 +73,30 @@ where
         .without_time()
         .init();
 
-    lambda_http::run(service_fn(|req: Request| async {
-        handle_request::<T>(req, app_pk).await
+    let config = &config;
+    lambda_http::run(service_fn(|req: Request| async move {
+        handle_request::<T>(req, config).await
     }))
     .await
 }
 
-async fn handle_request<T>(req: Request, app_pk: &str) -> Result<Response<Body>, Error>
+async fn handle_request<T>(req: Request, config: &Config) -> Result<Response<Body>, Error>
 where
-    T: InteractionHandler + Sync,
+    T: InteractionHandler + Sync + 'static,
 {
     let req_body = std::str::from_utf8(req.body()).unwrap();
     let headers = req.headers();
 
-    match verify(&req_body, headers, app_pk) {
+    match verify(req_body, headers, &config.app_pk) {
         Ok(()) => {
-            let res_body = handle_body::<T>(&req_body).unwrap_or("{}".to_string());
+            let (content_type, res_body) = handle_body::<T>(req_body, config)
+                .await
+                .unwrap_or(("application/json".to_string(), Body::Text("{}".to_string())));
 
             Ok(Response::builder()
                 .status(StatusCode::OK)
-                .header("Content-Type", "application/json")
-                .body(res_body.into())
+                .header("Content-Type", content_type)
+                .body(res_body)
                 .unwrap())
         }
 
@@ -48,29 +107,74 @@ where
     }
 }
 
-fn handle_body<T>(req_json: &str) -> Option<String>
+/// Handles the body of a verified request, returning the content type and body of the response.
+/// JSON bodies are returned as text, since a binary body is sent base64-encoded, which API Gateway
+/// only decodes for the media types it was configured to treat as binary.
+async fn handle_body<T>(req_json: &str, config: &Config) -> Option<(String, Body)>
 where
-    T: InteractionHandler + Sync,
+    T: InteractionHandler + Sync + 'static,
 {
     tracing::info!({ %req_json }, "Request JSON");
 
-    match serde_json::from_str::<super::discord_types::InteractionRequest>(req_json) {
-        Ok(interaction) => {
-            let res = super::handler::handle_interaction::<T>(&interaction);
+    let parsed = serde_json::from_str::<serde_json::Value>(req_json).and_then(|raw| {
+        let interaction = super::discord_types::InteractionRequest::deserialize(&raw)?;
+        Ok(super::discord_types::InteractionRequest { raw, ..interaction })
+    });
+    let interaction = match parsed {
+        Ok(interaction) => interaction,
+        Err(err) => {
+            tracing::error!({ %err }, "Could not parse request");
+            return None;
+        }
+    };
 
-            let res_json = json!(res).to_string();
+    let token = InteractionToken::from(&interaction);
+
+    let outcome = match config.deadline {
+        Some(deadline) => {
+            handle_with_deadline::<T>(interaction, deadline, config.transport.clone()).await
+        }
+        None => super::handler::handle_interaction::<T>(&interaction).map(Outcome::respond),
+    };
+
+    match outcome {
+        // Follow-ups can only be sent once the interaction has been acknowledged, so the response
+        // goes through the callback endpoint instead of the body of the HTTP response.
+        Ok(Outcome::Respond(res)) if !res.followups.is_empty() => {
+            let client = WebhookClient::with_transport(token, config.transport.clone());
+            if let Err(err) = client.respond(*res).await {
+                tracing::error!({ %err }, "Could not send response with follow-ups");
+            }
+            None
+        }
+
+        Ok(Outcome::Respond(res)) => {
+            let res_json = res.to_json();
 
             tracing::info!({ %res_json }, "Response JSON");
 
-            Some(res_json)
+            if res.files().is_empty() {
+                Some((
+                    "application/json".to_string(),
+                    Body::Text(res_json.to_string()),
+                ))
+            } else {
+                let (content_type, body) = multipart::encode(&res_json, res.files());
+                Some((content_type, Body::Binary(body)))
+            }
         }
 
-        Err(_) => None,
+        Ok(Outcome::Delivered) => None,
+
+        Err(err) => {
+            tracing::error!({ %err }, "Could not handle interaction");
+            None
+        }
     }
 }
 
 fn verify(body: &str, headers: &HeaderMap, app_pk: &str) -> Result<(), StatusCode> {
-    let application_public_key: [u8; PUBLIC_KEY_LENGTH] = hex::decode(&app_pk)
+    let application_public_key: [u8; PUBLIC_KEY_LENGTH] = hex::decode(app_pk)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
         .try_into()
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -90,7 +194,7 @@ fn verify(body: &str, headers: &HeaderMap, app_pk: &str) -> Result<(), StatusCod
         .to_str()
         .map_err(|_| StatusCode::BAD_REQUEST)?;
 
-    let signature: [u8; 64] = hex::decode(&signature_str)
+    let signature: [u8; 64] = hex::decode(signature_str)
         .map_err(|_| StatusCode::BAD_REQUEST)?
         .try_into()
         .map_err(|_| StatusCode::BAD_REQUEST)?;
@@ -3,24 +3,66 @@
  * match the JSON structure established by the Discord API.
  */
 
+use super::snowflake::Snowflake;
 use serde::{Deserialize, Serialize};
-use serde_repr::{Deserialize_repr, Serialize_repr};
+use std::collections::HashMap;
+
+/// Declares an enum that is sent as an integer. Values that Discord added after this library was
+/// written are kept in an `Unknown` variant, rather than failing to parse.
+macro_rules! integer_enum {
+    (pub enum $name:ident { $($variant:ident = $value:literal,)* }) => {
+        #[derive(Deserialize, Serialize, PartialEq, Debug, Clone, Copy)]
+        #[serde(from = "u8", into = "u8")]
+        pub enum $name {
+            $($variant,)*
+            Unknown(u8),
+        }
+
+        impl From<u8> for $name {
+            fn from(value: u8) -> Self {
+                match value {
+                    $($value => $name::$variant,)*
+                    other => $name::Unknown(other),
+                }
+            }
+        }
+
+        impl From<$name> for u8 {
+            fn from(value: $name) -> Self {
+                match value {
+                    $($name::$variant => $value,)*
+                    $name::Unknown(other) => other,
+                }
+            }
+        }
+    };
+}
 
 #[derive(Deserialize, PartialEq, Debug)]
 pub struct InteractionRequest {
+    pub id: Snowflake,
+    pub application_id: Snowflake,
+    pub token: String,
     pub r#type: InteractionType,
     pub data: Option<InteractionData>,
     pub member: Option<GuildMember>,
+    pub user: Option<User>,
     pub message: Option<Message>,
+    pub locale: Option<String>,
+    /// The whole request as raw JSON, filled in once the request has been parsed. Handlers can read
+    /// fields that this library doesn't model from it.
+    #[serde(skip)]
+    pub raw: serde_json::Value,
 }
 
-#[derive(Deserialize_repr, PartialEq, Debug)]
-#[repr(u8)]
-pub enum InteractionType {
-    Ping = 1,
-    ApplicationCommand = 2,
-    MessageComponent = 3,
-    ModalSubmit = 5,
+integer_enum! {
+    pub enum InteractionType {
+        Ping = 1,
+        ApplicationCommand = 2,
+        MessageComponent = 3,
+        ApplicationCommandAutocomplete = 4,
+        ModalSubmit = 5,
+    }
 }
 
 #[derive(Deserialize, PartialEq, Debug)]
@@ -28,6 +70,53 @@ pub struct InteractionData {
     pub name: Option<String>,
     pub custom_id: Option<String>,
     pub components: Option<Vec<Component>>,
+    pub options: Option<Vec<InteractionDataOption>>,
+    pub component_type: Option<ComponentType>,
+    pub values: Option<Vec<String>>,
+    pub resolved: Option<ResolvedData>,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Default)]
+pub struct ResolvedData {
+    #[serde(default)]
+    pub users: HashMap<Snowflake, User>,
+    #[serde(default)]
+    pub members: HashMap<Snowflake, PartialMember>,
+    #[serde(default)]
+    pub roles: HashMap<Snowflake, Role>,
+    #[serde(default)]
+    pub channels: HashMap<Snowflake, PartialChannel>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct PartialMember {
+    pub nick: Option<String>,
+    #[serde(default)]
+    pub roles: Vec<Snowflake>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct Role {
+    pub id: Snowflake,
+    pub name: String,
+    pub color: u32,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct PartialChannel {
+    pub id: Snowflake,
+    pub r#type: u8,
+    pub name: Option<String>,
+    pub parent_id: Option<Snowflake>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct InteractionDataOption {
+    pub name: String,
+    pub r#type: u8,
+    pub value: Option<serde_json::Value>,
+    pub options: Option<Vec<InteractionDataOption>>,
+    pub focused: Option<bool>,
 }
 
 #[derive(Deserialize, PartialEq, Debug)]
@@ -37,64 +126,462 @@ pub struct GuildMember {
 
 #[derive(Deserialize, PartialEq, Debug)]
 pub struct Message {
+    pub id: Snowflake,
+    pub channel_id: Snowflake,
+    pub author: User,
+    pub timestamp: String,
+    pub edited_timestamp: Option<String>,
     pub content: String,
+    #[serde(default)]
+    pub components: Vec<Component>,
+    pub interaction_metadata: Option<MessageInteractionMetadata>,
+    #[serde(default)]
+    pub embeds: Vec<Embed>,
+    pub flags: Option<u32>,
+    pub poll: Option<Poll>,
 }
 
 #[derive(Deserialize, PartialEq, Debug)]
 pub struct User {
-    pub id: String,
+    pub id: Snowflake,
+    pub username: Option<String>,
+    pub global_name: Option<String>,
+    pub bot: Option<bool>,
 }
 
 #[derive(Serialize, PartialEq, Debug)]
 pub struct InteractionResponse {
     pub r#type: InteractionCallbackType,
     pub data: Option<InteractionCallbackData>,
+    /// Messages to send through the interaction webhook once the response has been sent.
+    #[serde(skip)]
+    pub followups: Vec<InteractionCallbackData>,
+    /// A response given as raw JSON by the handler, which is sent in place of the other fields.
+    #[serde(skip)]
+    pub raw: Option<serde_json::Value>,
 }
 
-#[derive(Serialize_repr, PartialEq, Debug)]
-#[repr(u8)]
-pub enum InteractionCallbackType {
-    Pong = 1,
-    ChannelMessageWithSource = 4,
-    UpdateMessage = 7,
-    Modal = 9,
+integer_enum! {
+    pub enum InteractionCallbackType {
+        Pong = 1,
+        ChannelMessageWithSource = 4,
+        DeferredChannelMessageWithSource = 5,
+        DeferredUpdateMessage = 6,
+        UpdateMessage = 7,
+        ApplicationCommandAutocompleteResult = 8,
+        Modal = 9,
+    }
 }
 
-#[derive(Serialize, PartialEq, Debug)]
+#[derive(Serialize, PartialEq, Debug, Default)]
 pub struct InteractionCallbackData {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
-    pub flags: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flags: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub components: Option<Vec<Component>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub custom_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub choices: Option<Vec<CommandOptionChoice>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embeds: Option<Vec<Embed>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_mentions: Option<AllowedMentions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attachments: Option<Vec<PartialAttachment>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub poll: Option<PollCreateRequest>,
+    /// The contents of the files listed in `attachments`, sent as separate multipart form parts.
+    #[serde(skip)]
+    pub files: Vec<FileUpload>,
+}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct PartialAttachment {
+    pub id: usize,
+    pub filename: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct MessageInteractionMetadata {
+    pub id: Snowflake,
+    pub r#type: u8,
+    pub user: User,
+    pub original_response_message_id: Option<Snowflake>,
+    pub interacted_message_id: Option<Snowflake>,
+}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct PollCreateRequest {
+    pub question: PollMedia,
+    pub answers: Vec<PollAnswer>,
+    pub duration: u32,
+    pub allow_multiselect: bool,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct Poll {
+    pub question: PollMedia,
+    pub answers: Vec<PollAnswer>,
+    pub expiry: Option<String>,
+    #[serde(default)]
+    pub allow_multiselect: bool,
+    pub results: Option<PollResults>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct PollMedia {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emoji: Option<Emoji>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct PollAnswer {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub answer_id: Option<u32>,
+    pub poll_media: PollMedia,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct PollResults {
+    pub is_finalized: bool,
+    pub answer_counts: Vec<PollAnswerCount>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct PollAnswerCount {
+    pub id: u32,
+    pub count: u32,
+    pub me_voted: bool,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct FileUpload {
+    pub filename: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct AllowedMentions {
+    pub parse: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub users: Vec<Snowflake>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<Snowflake>,
+    pub replied_user: bool,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+pub struct Embed {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub footer: Option<EmbedFooter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<EmbedMedia>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<EmbedMedia>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<EmbedAuthor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<Vec<EmbedField>>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+pub struct EmbedFooter {
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+pub struct EmbedMedia {
+    pub url: String,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+pub struct EmbedAuthor {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+pub struct EmbedField {
+    pub name: String,
+    pub value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inline: Option<bool>,
+}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct CommandOptionChoice {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_localizations: Option<HashMap<String, String>>,
+    pub value: serde_json::Value,
 }
 
 #[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
 pub struct Component {
     pub r#type: ComponentType,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub style: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub custom_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub components: Option<Vec<Component>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emoji: Option<Emoji>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sku_id: Option<Snowflake>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<Vec<SelectOption>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_values: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_values: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_types: Option<Vec<u8>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_values: Option<Vec<SelectDefaultValue>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accessory: Option<Box<Component>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media: Option<UnfurledMedia>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spoiler: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<MediaGalleryItem>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<UnfurledMedia>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub divider: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spacing: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accent_color: Option<u32>,
+    /// Fields that this library doesn't model, which are sent back unchanged.
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl InteractionResponse {
+    /// Creates a response that is sent as the given raw JSON.
+    pub fn raw(raw: serde_json::Value) -> Self {
+        let r#type = raw["type"]
+            .as_u64()
+            .and_then(|t| u8::try_from(t).ok())
+            .unwrap_or(0);
+
+        InteractionResponse {
+            r#type: r#type.into(),
+            data: None,
+            followups: Vec::new(),
+            raw: Some(raw),
+        }
+    }
+
+    /// The JSON to send for the response.
+    pub fn to_json(&self) -> serde_json::Value {
+        match &self.raw {
+            Some(raw) => raw.clone(),
+            None => serde_json::json!(self),
+        }
+    }
+
+    /// The files to upload alongside the response, if any.
+    pub fn files(&self) -> &[FileUpload] {
+        self.data.as_ref().map_or(&[], |data| &data.files)
+    }
 }
 
-#[derive(Deserialize_repr, Serialize_repr, PartialEq, Debug, Clone)]
-#[repr(u8)]
-pub enum TextInputStyle {
-    Short = 1,
+impl Component {
+    /// Creates a component of the given type, with every other field left empty.
+    pub fn new(r#type: ComponentType) -> Self {
+        Component {
+            r#type,
+            label: None,
+            style: None,
+            custom_id: None,
+            value: None,
+            components: None,
+            url: None,
+            emoji: None,
+            disabled: None,
+            sku_id: None,
+            options: None,
+            placeholder: None,
+            min_values: None,
+            max_values: None,
+            channel_types: None,
+            default_values: None,
+            min_length: None,
+            max_length: None,
+            required: None,
+            content: None,
+            accessory: None,
+            media: None,
+            description: None,
+            spoiler: None,
+            items: None,
+            file: None,
+            divider: None,
+            spacing: None,
+            accent_color: None,
+            extra: HashMap::new(),
+        }
+    }
 }
 
-#[derive(Deserialize_repr, Serialize_repr, PartialEq, Debug, Clone)]
-#[repr(u8)]
-pub enum ComponentType {
-    ActionRow = 1,
-    Button = 2,
-    TextInput = 4,
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+pub struct UnfurledMedia {
+    pub url: String,
 }
 
-#[derive(Deserialize_repr, Serialize_repr, PartialEq, Debug, Clone)]
-#[repr(u8)]
-pub enum ButtonStyle {
-    Primary = 1,
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+pub struct MediaGalleryItem {
+    pub media: UnfurledMedia,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spoiler: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+pub struct SelectOption {
+    pub label: String,
+    pub value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emoji: Option<Emoji>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+pub struct SelectDefaultValue {
+    pub id: Snowflake,
+    pub r#type: String,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+pub struct Emoji {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Snowflake>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub animated: Option<bool>,
+}
+
+integer_enum! {
+    pub enum TextInputStyle {
+        Short = 1,
+        Paragraph = 2,
+    }
+}
+
+integer_enum! {
+    pub enum ComponentType {
+        ActionRow = 1,
+        Button = 2,
+        StringSelect = 3,
+        TextInput = 4,
+        UserSelect = 5,
+        RoleSelect = 6,
+        MentionableSelect = 7,
+        ChannelSelect = 8,
+        Section = 9,
+        TextDisplay = 10,
+        Thumbnail = 11,
+        MediaGallery = 12,
+        File = 13,
+        Separator = 14,
+        Container = 17,
+    }
+}
+
+integer_enum! {
+    pub enum SeparatorSpacing {
+        Small = 1,
+        Large = 2,
+    }
+}
+
+integer_enum! {
+    pub enum ButtonStyle {
+        Primary = 1,
+        Secondary = 2,
+        Success = 3,
+        Danger = 4,
+        Link = 5,
+        Premium = 6,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn unknown_enum_values_are_kept() {
+        let r#type: ComponentType = serde_json::from_value(json!(99)).unwrap();
+
+        assert_eq!(r#type, ComponentType::Unknown(99));
+        assert_eq!(serde_json::to_value(r#type).unwrap(), json!(99));
+    }
+
+    #[test]
+    fn requests_of_an_unknown_type_still_parse() {
+        let req: InteractionRequest = serde_json::from_value(json!({
+            "id": "1",
+            "application_id": "2",
+            "token": "t",
+            "type": 42,
+        }))
+        .unwrap();
+
+        assert_eq!(req.r#type, InteractionType::Unknown(42));
+    }
 }
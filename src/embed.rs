@@ -0,0 +1,305 @@
+/*!
+ * Rich embeds, which show formatted content such as titles, fields, and images below a message's
+ * text. Discord limits the length of each part of an embed, as well as the combined length of all
+ * embeds in a message, so embeds are checked against those limits before they are sent.
+ */
+
+use super::discord_types;
+use super::user_types::{check_count, check_length, ValidationError};
+
+/// The most embeds that a single message can hold.
+const MAX_EMBEDS: usize = 10;
+
+/// The most characters that all of a message's embeds can hold combined.
+const MAX_TOTAL_CHARS: usize = 6000;
+
+/// A rich embed, attached to a message.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Embed {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// The URL that the title links to.
+    pub url: Option<String>,
+    /// The color of the embed's left border, as an RGB integer such as `0x5865F2`.
+    pub color: Option<u32>,
+    /// An ISO 8601 timestamp, such as `2024-01-01T12:00:00Z`, shown next to the footer.
+    pub timestamp: Option<String>,
+    pub footer: Option<EmbedFooter>,
+    /// The URL of a large image shown at the bottom of the embed.
+    pub image: Option<String>,
+    /// The URL of a small image shown at the top right of the embed.
+    pub thumbnail: Option<String>,
+    pub author: Option<EmbedAuthor>,
+    pub fields: Vec<EmbedField>,
+}
+
+/// The footer of an embed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EmbedFooter {
+    pub text: String,
+    pub icon_url: Option<String>,
+}
+
+/// The author shown at the top of an embed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EmbedAuthor {
+    pub name: String,
+    /// The URL that the author's name links to.
+    pub url: Option<String>,
+    pub icon_url: Option<String>,
+}
+
+/// A titled field in an embed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EmbedField {
+    pub name: String,
+    pub value: String,
+    /// If true, the field may be shown side by side with neighboring inline fields.
+    pub inline: bool,
+}
+
+/// Convenience methods for building embeds.
+impl Embed {
+    /// Creates a new, empty embed.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the title of the embed.
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Sets the description of the embed, shown below the title.
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Sets the URL that the title links to.
+    pub fn url(mut self, url: &str) -> Self {
+        self.url = Some(url.to_string());
+        self
+    }
+
+    /// Sets the color of the embed's left border, as an RGB integer.
+    pub fn color(mut self, color: u32) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Sets the ISO 8601 timestamp shown next to the footer.
+    pub fn timestamp(mut self, timestamp: &str) -> Self {
+        self.timestamp = Some(timestamp.to_string());
+        self
+    }
+
+    /// Sets the text of the footer.
+    pub fn footer(mut self, text: &str) -> Self {
+        let icon_url = self.footer.and_then(|f| f.icon_url);
+        self.footer = Some(EmbedFooter {
+            text: text.to_string(),
+            icon_url,
+        });
+        self
+    }
+
+    /// Sets the icon shown next to the footer's text.
+    pub fn footer_icon(mut self, icon_url: &str) -> Self {
+        let text = self.footer.map(|f| f.text).unwrap_or_default();
+        self.footer = Some(EmbedFooter {
+            text,
+            icon_url: Some(icon_url.to_string()),
+        });
+        self
+    }
+
+    /// Sets the URL of the large image shown at the bottom of the embed.
+    pub fn image(mut self, url: &str) -> Self {
+        self.image = Some(url.to_string());
+        self
+    }
+
+    /// Sets the URL of the small image shown at the top right of the embed.
+    pub fn thumbnail(mut self, url: &str) -> Self {
+        self.thumbnail = Some(url.to_string());
+        self
+    }
+
+    /// Sets the name of the author.
+    pub fn author(mut self, name: &str) -> Self {
+        self.author_mut().name = name.to_string();
+        self
+    }
+
+    /// Sets the URL that the author's name links to.
+    pub fn author_url(mut self, url: &str) -> Self {
+        self.author_mut().url = Some(url.to_string());
+        self
+    }
+
+    /// Sets the icon shown next to the author's name.
+    pub fn author_icon(mut self, icon_url: &str) -> Self {
+        self.author_mut().icon_url = Some(icon_url.to_string());
+        self
+    }
+
+    /// Adds a field to the embed.
+    pub fn field(mut self, name: &str, value: &str) -> Self {
+        self.fields.push(EmbedField {
+            name: name.to_string(),
+            value: value.to_string(),
+            inline: false,
+        });
+        self
+    }
+
+    /// Adds a field to the embed, which may be shown side by side with other inline fields.
+    pub fn inline_field(mut self, name: &str, value: &str) -> Self {
+        self.fields.push(EmbedField {
+            name: name.to_string(),
+            value: value.to_string(),
+            inline: true,
+        });
+        self
+    }
+
+    /// Sets the value of the field with the given name, or adds the field if the embed has no
+    /// field by that name.
+    pub fn set_field(mut self, name: &str, value: &str) -> Self {
+        match self.fields.iter_mut().find(|f| f.name == name) {
+            Some(field) => field.value = value.to_string(),
+            None => return self.field(name, value),
+        }
+        self
+    }
+
+    fn author_mut(&mut self) -> &mut EmbedAuthor {
+        self.author.get_or_insert_with(|| EmbedAuthor {
+            name: "".to_string(),
+            url: None,
+            icon_url: None,
+        })
+    }
+
+    /// Checks the embed against Discord's limits, returning the number of characters it holds.
+    fn validate(&self) -> Result<usize, ValidationError> {
+        let mut total = 0;
+        let mut count = |what, s: &str, min, max| {
+            check_length(what, s, min, max)?;
+            total += s.chars().count();
+            Ok::<(), ValidationError>(())
+        };
+
+        if let Some(title) = &self.title {
+            count("embed title", title, 1, 256)?;
+        }
+        if let Some(description) = &self.description {
+            count("embed description", description, 1, 4096)?;
+        }
+        if let Some(footer) = &self.footer {
+            count("embed footer text", &footer.text, 1, 2048)?;
+        }
+        if let Some(author) = &self.author {
+            count("embed author name", &author.name, 1, 256)?;
+        }
+
+        check_count("embed fields", self.fields.len(), 25)?;
+        for field in &self.fields {
+            count("embed field name", &field.name, 1, 256)?;
+            count("embed field value", &field.value, 1, 1024)?;
+        }
+
+        Ok(total)
+    }
+}
+
+/// Checks a message's embeds against Discord's limits, and converts them for sending.
+pub(crate) fn embeds_into_wire(
+    embeds: Vec<Embed>,
+) -> Result<Vec<discord_types::Embed>, ValidationError> {
+    check_count("embeds", embeds.len(), MAX_EMBEDS)?;
+
+    let mut total = 0;
+    for embed in &embeds {
+        total += embed.validate()?;
+    }
+    if total > MAX_TOTAL_CHARS {
+        return Err(ValidationError::Length {
+            what: "the combined text of all embeds",
+            min: 0,
+            max: MAX_TOTAL_CHARS,
+            found: total,
+        });
+    }
+
+    Ok(embeds.into_iter().map(|e| e.into()).collect())
+}
+
+impl From<Embed> for discord_types::Embed {
+    fn from(embed: Embed) -> Self {
+        discord_types::Embed {
+            title: embed.title,
+            description: embed.description,
+            url: embed.url,
+            timestamp: embed.timestamp,
+            color: embed.color,
+            footer: embed.footer.map(|f| discord_types::EmbedFooter {
+                text: f.text,
+                icon_url: f.icon_url,
+            }),
+            image: embed.image.map(|url| discord_types::EmbedMedia { url }),
+            thumbnail: embed.thumbnail.map(|url| discord_types::EmbedMedia { url }),
+            author: embed.author.map(|a| discord_types::EmbedAuthor {
+                name: a.name,
+                url: a.url,
+                icon_url: a.icon_url,
+            }),
+            fields: (!embed.fields.is_empty()).then(|| {
+                embed
+                    .fields
+                    .into_iter()
+                    .map(|f| discord_types::EmbedField {
+                        name: f.name,
+                        value: f.value,
+                        inline: f.inline.then_some(true),
+                    })
+                    .collect()
+            }),
+        }
+    }
+}
+
+impl From<&discord_types::Embed> for Embed {
+    fn from(embed: &discord_types::Embed) -> Self {
+        Embed {
+            title: embed.title.clone(),
+            description: embed.description.clone(),
+            url: embed.url.clone(),
+            color: embed.color,
+            timestamp: embed.timestamp.clone(),
+            footer: embed.footer.as_ref().map(|f| EmbedFooter {
+                text: f.text.clone(),
+                icon_url: f.icon_url.clone(),
+            }),
+            image: embed.image.as_ref().map(|i| i.url.clone()),
+            thumbnail: embed.thumbnail.as_ref().map(|t| t.url.clone()),
+            author: embed.author.as_ref().map(|a| EmbedAuthor {
+                name: a.name.clone(),
+                url: a.url.clone(),
+                icon_url: a.icon_url.clone(),
+            }),
+            fields: embed
+                .fields
+                .iter()
+                .flatten()
+                .map(|f| EmbedField {
+                    name: f.name.clone(),
+                    value: f.value.clone(),
+                    inline: f.inline.unwrap_or(false),
+                })
+                .collect(),
+        }
+    }
+}
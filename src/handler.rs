@@ -1,9 +1,13 @@
 use super::discord_types::{
     InteractionCallbackType, InteractionRequest, InteractionResponse, InteractionType,
 };
-use super::user_types::{ApplicationCommand, MessageComponent, ModalSubmit, Response};
+use super::user_types::{
+    ApplicationCommand, Autocomplete, Choice, ConversionError, Message, MessageComponent,
+    ModalSubmit, Response, ValidationError,
+};
+use std::fmt;
 
-/// General interaction handler type, to be implemented by your application. To implement this trait, you must at minimum be able to handle incoming application commands (slash commands). If your application involves buttons or modal inputs, you should implement the corresponding trait functions as well.
+/// General interaction handler type, to be implemented by your application. To implement this trait, you must at minimum be able to handle incoming application commands (slash commands). If your application involves buttons, modal inputs, or autocompleted command options, you should implement the corresponding trait functions as well.
 pub trait InteractionHandler {
     #[allow(unused)]
     fn handle_application_command(ac: ApplicationCommand) -> Response;
@@ -17,31 +21,213 @@ pub trait InteractionHandler {
     fn handle_modal_submit(ms: ModalSubmit) -> Response {
         todo!();
     }
+
+    /// Replies to a user who used a component of a message restricted with
+    /// `Message::invoker_only`, but who isn't the user that the message responds to. The reply is
+    /// always sent as a new, ephemeral message, and `handle_message_component` is not called.
+    #[allow(unused)]
+    fn handle_foreign_component(mc: MessageComponent) -> Message {
+        Message::new().text("Only the user who invoked this command can use these components.")
+    }
+
+    /// Suggests choices for the option that the user is currently typing into. At most 25 choices
+    /// may be returned. By default, no choices are suggested.
+    #[allow(unused)]
+    fn handle_autocomplete(ac: Autocomplete) -> Vec<Choice> {
+        Vec::new()
+    }
+}
+
+/// An error raised while handling an interaction, in which case no response is sent.
+#[derive(Debug, PartialEq)]
+pub enum HandleError {
+    /// The request could not be converted for the handler.
+    Conversion(ConversionError),
+    /// The handler's response breaks one of Discord's limits.
+    Validation(ValidationError),
+}
+
+impl fmt::Display for HandleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HandleError::Conversion(err) => write!(f, "invalid request: {err}"),
+            HandleError::Validation(err) => write!(f, "invalid response: {err}"),
+        }
+    }
 }
 
-pub fn handle_interaction<T>(req: &InteractionRequest) -> InteractionResponse
+impl std::error::Error for HandleError {}
+
+impl From<ConversionError> for HandleError {
+    fn from(err: ConversionError) -> Self {
+        HandleError::Conversion(err)
+    }
+}
+
+impl From<ValidationError> for HandleError {
+    fn from(err: ValidationError) -> Self {
+        HandleError::Validation(err)
+    }
+}
+
+pub fn handle_interaction<T>(req: &InteractionRequest) -> Result<InteractionResponse, HandleError>
 where
     T: InteractionHandler,
 {
-    match req.r#type {
-        InteractionType::Ping => InteractionResponse {
+    let res = match req.r#type {
+        InteractionType::Ping => Ok(InteractionResponse {
             r#type: InteractionCallbackType::Pong,
             data: None,
-        },
-
-        InteractionType::ApplicationCommand => match T::handle_application_command(req.into()) {
-            Response::Message(m) => m.into(),
-            Response::Modal(m) => m.into(),
-        },
-
-        InteractionType::MessageComponent => match T::handle_message_component(req.into()) {
-            Response::Message(m) => m.into(),
-            Response::Modal(m) => m.into(),
-        },
-
-        InteractionType::ModalSubmit => match T::handle_modal_submit(req.into()) {
-            Response::Message(m) => m.into(),
-            Response::Modal(_) => panic!("Modal cannot result in another modal!"),
-        },
+            followups: Vec::new(),
+            raw: None,
+        }),
+
+        InteractionType::ApplicationCommand => {
+            match T::handle_application_command(req.try_into()?) {
+                Response::DeferUpdate | Response::Acknowledge { .. } => Err(
+                    ValidationError::Unsupported("an application command has no message to update"),
+                ),
+                res => res.try_into(),
+            }
+        }
+
+        InteractionType::MessageComponent => {
+            let mc = MessageComponent::try_from(req)?;
+            if mc.source.invoker_only && !mc.is_from_invoker() {
+                let mut reply = T::handle_foreign_component(mc).ephemeral();
+                reply.edit = false;
+                Ok(reply.try_into()?)
+            } else {
+                T::handle_message_component(mc).try_into()
+            }
+        }
+
+        InteractionType::ApplicationCommandAutocomplete => {
+            T::handle_autocomplete(req.try_into()?).try_into()
+        }
+
+        InteractionType::ModalSubmit => {
+            let ms = ModalSubmit::try_from(req)?;
+            let has_source = ms.source.is_some();
+
+            match T::handle_modal_submit(ms) {
+                Response::Modal(_) => Err(ValidationError::Unsupported(
+                    "a modal cannot result in another modal",
+                )),
+                Response::Message(Message { edit: true, .. })
+                | Response::DeferUpdate
+                | Response::Acknowledge { .. }
+                    if !has_source =>
+                {
+                    Err(ValidationError::Unsupported(
+                        "a modal opened from an application command has no message to update",
+                    ))
+                }
+                res => res.try_into(),
+            }
+        }
+
+        InteractionType::Unknown(t) => return Err(ConversionError::UnknownType(t).into()),
+    };
+
+    Ok(res?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user_types::MessageFlags;
+    use serde_json::json;
+
+    struct Unreachable;
+
+    impl InteractionHandler for Unreachable {
+        fn handle_application_command(_: ApplicationCommand) -> Response {
+            unreachable!("the handler should not be called");
+        }
+
+        fn handle_message_component(_: MessageComponent) -> Response {
+            unreachable!("the handler should not be called");
+        }
+    }
+
+    struct Clicked;
+
+    impl InteractionHandler for Clicked {
+        fn handle_application_command(_: ApplicationCommand) -> Response {
+            unreachable!("the handler should not be called");
+        }
+
+        fn handle_message_component(_: MessageComponent) -> Response {
+            Response::Message(Message::new().text("clicked").edit())
+        }
+    }
+
+    fn request(value: serde_json::Value) -> InteractionRequest {
+        serde_json::from_value(value).unwrap()
+    }
+
+    /// A click by `user` on a button of an invoker-only message that responds to user 5.
+    fn invoker_only_click(user: &str) -> InteractionRequest {
+        request(json!({
+            "id": "1",
+            "application_id": "2",
+            "token": "t",
+            "type": 3,
+            "data": { "custom_id": "~owner~b", "component_type": 2 },
+            "member": { "user": { "id": user } },
+            "message": {
+                "id": "3",
+                "channel_id": "4",
+                "author": { "id": "2" },
+                "timestamp": "2024-01-01T00:00:00Z",
+                "edited_timestamp": null,
+                "content": "hello",
+                "components": [{
+                    "type": 1,
+                    "components": [{ "type": 2, "style": 1, "custom_id": "~owner~b" }],
+                }],
+                "interaction_metadata": { "id": "6", "type": 2, "user": { "id": "5" } },
+            },
+        }))
+    }
+
+    #[test]
+    fn foreign_users_of_invoker_only_components_get_an_ephemeral_reply() {
+        let res = handle_interaction::<Unreachable>(&invoker_only_click("7")).unwrap();
+
+        assert_eq!(
+            res.r#type,
+            InteractionCallbackType::ChannelMessageWithSource
+        );
+        let data = res.data.unwrap();
+        assert_eq!(data.flags, Some(MessageFlags::EPHEMERAL.bits()));
+        assert_eq!(
+            data.content.as_deref(),
+            Some("Only the user who invoked this command can use these components.")
+        );
+    }
+
+    #[test]
+    fn invokers_of_invoker_only_components_reach_the_handler() {
+        let res = handle_interaction::<Clicked>(&invoker_only_click("5")).unwrap();
+
+        assert_eq!(res.r#type, InteractionCallbackType::UpdateMessage);
+        assert_eq!(res.data.unwrap().content.as_deref(), Some("clicked"));
+    }
+
+    #[test]
+    fn unknown_interaction_types_are_an_error() {
+        let req = request(json!({
+            "id": "1",
+            "application_id": "2",
+            "token": "t",
+            "type": 42,
+        }));
+
+        assert_eq!(
+            handle_interaction::<Unreachable>(&req),
+            Err(HandleError::Conversion(ConversionError::UnknownType(42)))
+        );
     }
 }
@@ -0,0 +1,620 @@
+/*!
+ * Layout components, which let a message be laid out as a tree of sections, containers, text,
+ * media, and separators, in place of plain text and embeds. A message with a layout is sent with
+ * Discord's `IS_COMPONENTS_V2` flag, under which the message cannot also have text or embeds.
+ */
+
+use super::discord_types;
+use super::user_types::{
+    check_count, check_length, parse_button, parse_row, unknown_from_wire, unknown_into_wire,
+    ActionRow, Button, SelectMenu, ValidationError,
+};
+
+/// The most components that a message with a layout can hold, counting nested ones.
+const MAX_COMPONENTS: usize = 40;
+
+/// The most characters that all of a message's text displays can hold combined.
+const MAX_TOTAL_TEXT: usize = 4000;
+
+/// A component in a message's layout.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LayoutComponent {
+    /// A row of buttons or a select menu.
+    Row(ActionRow),
+    /// Up to 3 pieces of text, shown next to a thumbnail or a button.
+    Section(Section),
+    /// Markdown text.
+    Text(String),
+    /// A grid of up to 10 images or videos.
+    MediaGallery(Vec<Media>),
+    /// A file uploaded with the message, referenced with an `attachment://<filename>` URL.
+    File { url: String, spoiler: bool },
+    /// Vertical space between components, optionally with a dividing line.
+    Separator(Separator),
+    /// A box around other components, with an optional accent color along its left border.
+    Container(Container),
+    /// A component of a received message that this library doesn't know, such as one that
+    /// Discord added later. It is kept as raw JSON, and sent back unchanged.
+    Unknown(serde_json::Value),
+}
+
+/// A section, which shows up to 3 pieces of text next to an accessory.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Section {
+    pub texts: Vec<String>,
+    pub accessory: Accessory,
+}
+
+/// The component shown next to a section's text.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Accessory {
+    Thumbnail(Media),
+    Button(Button),
+}
+
+/// An image or video, shown as a thumbnail or as an item of a media gallery.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Media {
+    /// The URL of the media, which may be an `attachment://<filename>` URL.
+    pub url: String,
+    /// Alt text for the media.
+    pub description: Option<String>,
+    /// If true, the media is blurred until the user clicks it.
+    pub spoiler: bool,
+}
+
+/// Vertical space between components.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Separator {
+    /// If true, a dividing line is drawn in the middle of the space.
+    pub divider: bool,
+    pub spacing: Spacing,
+}
+
+/// The amount of space taken up by a separator.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Spacing {
+    Small,
+    Large,
+}
+
+/// A container, which draws a box around the components it holds. Containers cannot be nested.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Container {
+    pub components: Vec<LayoutComponent>,
+    /// The color of the container's left border, as an RGB integer such as `0x5865F2`.
+    pub accent_color: Option<u32>,
+    /// If true, the container's contents are blurred until the user clicks them.
+    pub spoiler: bool,
+}
+
+/// Convenience methods for building layout components.
+impl LayoutComponent {
+    /// Creates a text display, showing the given markdown text.
+    pub fn text(text: &str) -> Self {
+        LayoutComponent::Text(text.to_string())
+    }
+
+    /// Creates a media gallery, showing the given images or videos.
+    pub fn gallery(items: Vec<Media>) -> Self {
+        LayoutComponent::MediaGallery(items)
+    }
+
+    /// Creates a file component, showing the uploaded file with the given filename.
+    pub fn file(filename: &str) -> Self {
+        LayoutComponent::File {
+            url: format!("attachment://{filename}"),
+            spoiler: false,
+        }
+    }
+
+    /// Creates a small separator with a dividing line.
+    pub fn separator() -> Self {
+        LayoutComponent::Separator(Separator::default())
+    }
+
+    pub(crate) fn for_each_button(&mut self, f: &mut dyn FnMut(&mut Button)) {
+        match self {
+            LayoutComponent::Row(row) => row.for_each_button(f),
+            LayoutComponent::Section(Section {
+                accessory: Accessory::Button(button),
+                ..
+            }) => f(button),
+            LayoutComponent::Container(container) => {
+                for component in &mut container.components {
+                    component.for_each_button(f);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn for_each_select(&mut self, f: &mut dyn FnMut(&mut SelectMenu)) {
+        match self {
+            LayoutComponent::Row(row) => row.for_each_select(f),
+            LayoutComponent::Container(container) => {
+                for component in &mut container.components {
+                    component.for_each_select(f);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl From<ActionRow> for LayoutComponent {
+    fn from(row: ActionRow) -> Self {
+        LayoutComponent::Row(row)
+    }
+}
+
+impl From<Section> for LayoutComponent {
+    fn from(section: Section) -> Self {
+        LayoutComponent::Section(section)
+    }
+}
+
+impl From<Separator> for LayoutComponent {
+    fn from(separator: Separator) -> Self {
+        LayoutComponent::Separator(separator)
+    }
+}
+
+impl From<Container> for LayoutComponent {
+    fn from(container: Container) -> Self {
+        LayoutComponent::Container(container)
+    }
+}
+
+/// Convenience methods for building sections.
+impl Section {
+    /// Creates a section with a thumbnail of the given image next to its text.
+    pub fn with_thumbnail(thumbnail: Media) -> Self {
+        Section {
+            texts: Vec::new(),
+            accessory: Accessory::Thumbnail(thumbnail),
+        }
+    }
+
+    /// Creates a section with the given button next to its text.
+    pub fn with_button(button: Button) -> Self {
+        Section {
+            texts: Vec::new(),
+            accessory: Accessory::Button(button),
+        }
+    }
+
+    /// Adds a piece of markdown text to the section. A section holds 1 to 3 pieces of text.
+    pub fn text(mut self, text: &str) -> Self {
+        self.texts.push(text.to_string());
+        self
+    }
+}
+
+/// Convenience methods for building media.
+impl Media {
+    /// Creates media shown from the given URL.
+    pub fn new(url: &str) -> Self {
+        Media {
+            url: url.to_string(),
+            description: None,
+            spoiler: false,
+        }
+    }
+
+    /// Sets the alt text of the media.
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Blurs the media until the user clicks it.
+    pub fn spoiler(mut self) -> Self {
+        self.spoiler = true;
+        self
+    }
+}
+
+impl Default for Separator {
+    fn default() -> Self {
+        Separator {
+            divider: true,
+            spacing: Spacing::Small,
+        }
+    }
+}
+
+/// Convenience methods for building separators.
+impl Separator {
+    /// Creates a small separator with a dividing line.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Leaves out the dividing line, so the separator only adds space.
+    pub fn invisible(mut self) -> Self {
+        self.divider = false;
+        self
+    }
+
+    /// Makes the separator take up more space.
+    pub fn large(mut self) -> Self {
+        self.spacing = Spacing::Large;
+        self
+    }
+}
+
+impl Default for Container {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Convenience methods for building containers.
+impl Container {
+    /// Creates a new, empty container.
+    pub fn new() -> Self {
+        Container {
+            components: Vec::new(),
+            accent_color: None,
+            spoiler: false,
+        }
+    }
+
+    /// Adds a component to the container.
+    pub fn component(mut self, component: impl Into<LayoutComponent>) -> Self {
+        self.components.push(component.into());
+        self
+    }
+
+    /// Adds a text display, showing the given markdown text, to the container.
+    pub fn text(self, text: &str) -> Self {
+        self.component(LayoutComponent::text(text))
+    }
+
+    /// Sets the color of the container's left border, as an RGB integer.
+    pub fn accent_color(mut self, color: u32) -> Self {
+        self.accent_color = Some(color);
+        self
+    }
+
+    /// Blurs the container's contents until the user clicks them.
+    pub fn spoiler(mut self) -> Self {
+        self.spoiler = true;
+        self
+    }
+}
+
+/// Checks a message's layout against Discord's limits, and converts it for sending. The message's
+/// action rows, if any, are placed below the layout.
+pub(crate) fn layout_into_wire(
+    layout: &[LayoutComponent],
+    rows: &[ActionRow],
+) -> Result<Vec<discord_types::Component>, ValidationError> {
+    let components: Vec<discord_types::Component> = layout
+        .iter()
+        .map(|c| c.try_into())
+        .chain(rows.iter().map(|row| row.try_into()))
+        .collect::<Result<_, ValidationError>>()?;
+
+    let (count, text) = components
+        .iter()
+        .map(measure)
+        .fold((0, 0), |(c, t), (c2, t2)| (c + c2, t + t2));
+
+    check_count("components in a layout", count, MAX_COMPONENTS)?;
+    if text > MAX_TOTAL_TEXT {
+        return Err(ValidationError::Length {
+            what: "the combined text of all text displays",
+            min: 0,
+            max: MAX_TOTAL_TEXT,
+            found: text,
+        });
+    }
+
+    Ok(components)
+}
+
+/// Parses a layout component of a received message, keeping it as raw JSON if this library doesn't
+/// know it, or doesn't know some part of it.
+pub(crate) fn parse_layout(component: &discord_types::Component) -> LayoutComponent {
+    parse_known_layout(component)
+        .unwrap_or_else(|| LayoutComponent::Unknown(unknown_from_wire(component)))
+}
+
+/// Parses a layout component of a received message, or returns `None` if this library doesn't
+/// know it.
+fn parse_known_layout(component: &discord_types::Component) -> Option<LayoutComponent> {
+    use discord_types::ComponentType;
+
+    let media = |c: &discord_types::Component| {
+        Some(Media {
+            url: c.media.as_ref()?.url.clone(),
+            description: c.description.clone(),
+            spoiler: c.spoiler.unwrap_or(false),
+        })
+    };
+
+    Some(match component.r#type {
+        ComponentType::ActionRow => LayoutComponent::Row(parse_row(component)?),
+
+        ComponentType::Section => {
+            let accessory = component.accessory.as_deref()?;
+            let accessory = match accessory.r#type {
+                ComponentType::Thumbnail => Accessory::Thumbnail(media(accessory)?),
+                _ => Accessory::Button(parse_button(accessory)?),
+            };
+
+            LayoutComponent::Section(Section {
+                texts: component
+                    .components
+                    .iter()
+                    .flatten()
+                    .map(|c| match c.r#type {
+                        ComponentType::TextDisplay => c.content.clone(),
+                        _ => None,
+                    })
+                    .collect::<Option<_>>()?,
+                accessory,
+            })
+        }
+
+        ComponentType::TextDisplay => LayoutComponent::Text(component.content.clone()?),
+
+        ComponentType::MediaGallery => LayoutComponent::MediaGallery(
+            component
+                .items
+                .iter()
+                .flatten()
+                .map(|item| Media {
+                    url: item.media.url.clone(),
+                    description: item.description.clone(),
+                    spoiler: item.spoiler.unwrap_or(false),
+                })
+                .collect(),
+        ),
+
+        ComponentType::File => LayoutComponent::File {
+            url: component.file.as_ref()?.url.clone(),
+            spoiler: component.spoiler.unwrap_or(false),
+        },
+
+        ComponentType::Separator => LayoutComponent::Separator(Separator {
+            divider: component.divider.unwrap_or(true),
+            spacing: match component.spacing.map(discord_types::SeparatorSpacing::from) {
+                None | Some(discord_types::SeparatorSpacing::Small) => Spacing::Small,
+                Some(discord_types::SeparatorSpacing::Large) => Spacing::Large,
+                Some(discord_types::SeparatorSpacing::Unknown(_)) => return None,
+            },
+        }),
+
+        ComponentType::Container => LayoutComponent::Container(Container {
+            components: component
+                .components
+                .iter()
+                .flatten()
+                .map(parse_layout)
+                .collect(),
+            accent_color: component.accent_color,
+            spoiler: component.spoiler.unwrap_or(false),
+        }),
+
+        _ => return None,
+    })
+}
+
+/// Counts the components in a tree, along with the characters held by its text displays.
+fn measure(component: &discord_types::Component) -> (usize, usize) {
+    let own_text = component
+        .content
+        .as_ref()
+        .map_or(0, |content| content.chars().count());
+
+    component
+        .components
+        .iter()
+        .flatten()
+        .chain(component.accessory.as_deref())
+        .map(measure)
+        .fold((1, own_text), |(c, t), (c2, t2)| (c + c2, t + t2))
+}
+
+impl TryFrom<&LayoutComponent> for discord_types::Component {
+    type Error = ValidationError;
+
+    fn try_from(component: &LayoutComponent) -> Result<Self, Self::Error> {
+        use discord_types::ComponentType;
+
+        Ok(match component {
+            LayoutComponent::Row(row) => row.try_into()?,
+
+            LayoutComponent::Unknown(unknown) => unknown_into_wire(unknown)?,
+
+            LayoutComponent::Section(section) => {
+                if section.texts.is_empty() {
+                    return Err(ValidationError::Invalid(
+                        "a section must hold at least one piece of text",
+                    ));
+                }
+                check_count("pieces of text in a section", section.texts.len(), 3)?;
+
+                let texts = section
+                    .texts
+                    .iter()
+                    .map(|text| text_display(text))
+                    .collect::<Result<_, ValidationError>>()?;
+
+                let accessory = match &section.accessory {
+                    Accessory::Thumbnail(media) => discord_types::Component {
+                        media: Some(unfurled(&media.url)),
+                        description: media_description(media)?,
+                        spoiler: media.spoiler.then_some(true),
+                        ..discord_types::Component::new(ComponentType::Thumbnail)
+                    },
+                    Accessory::Button(button) => button.try_into()?,
+                };
+
+                discord_types::Component {
+                    components: Some(texts),
+                    accessory: Some(Box::new(accessory)),
+                    ..discord_types::Component::new(ComponentType::Section)
+                }
+            }
+
+            LayoutComponent::Text(text) => text_display(text)?,
+
+            LayoutComponent::MediaGallery(items) => {
+                if items.is_empty() {
+                    return Err(ValidationError::Invalid(
+                        "a media gallery must hold at least one item",
+                    ));
+                }
+                check_count("items in a media gallery", items.len(), 10)?;
+
+                let items = items
+                    .iter()
+                    .map(|media| {
+                        Ok(discord_types::MediaGalleryItem {
+                            media: unfurled(&media.url),
+                            description: media_description(media)?,
+                            spoiler: media.spoiler.then_some(true),
+                        })
+                    })
+                    .collect::<Result<_, ValidationError>>()?;
+
+                discord_types::Component {
+                    items: Some(items),
+                    ..discord_types::Component::new(ComponentType::MediaGallery)
+                }
+            }
+
+            LayoutComponent::File { url, spoiler } => {
+                if !url.starts_with("attachment://") {
+                    return Err(ValidationError::Invalid(
+                        "a file component must refer to an attachment:// url",
+                    ));
+                }
+
+                discord_types::Component {
+                    file: Some(unfurled(url)),
+                    spoiler: spoiler.then_some(true),
+                    ..discord_types::Component::new(ComponentType::File)
+                }
+            }
+
+            LayoutComponent::Separator(separator) => discord_types::Component {
+                divider: Some(separator.divider),
+                spacing: Some(discord_types::SeparatorSpacing::from(separator.spacing).into()),
+                ..discord_types::Component::new(ComponentType::Separator)
+            },
+
+            LayoutComponent::Container(container) => {
+                if container.components.is_empty() {
+                    return Err(ValidationError::Invalid(
+                        "a container must hold at least one component",
+                    ));
+                }
+                if container
+                    .components
+                    .iter()
+                    .any(|c| matches!(c, LayoutComponent::Container(_)))
+                {
+                    return Err(ValidationError::Invalid(
+                        "a container cannot hold another container",
+                    ));
+                }
+
+                let components = container
+                    .components
+                    .iter()
+                    .map(|c| c.try_into())
+                    .collect::<Result<_, ValidationError>>()?;
+
+                discord_types::Component {
+                    components: Some(components),
+                    accent_color: container.accent_color,
+                    spoiler: container.spoiler.then_some(true),
+                    ..discord_types::Component::new(ComponentType::Container)
+                }
+            }
+        })
+    }
+}
+
+impl From<Spacing> for discord_types::SeparatorSpacing {
+    fn from(spacing: Spacing) -> Self {
+        match spacing {
+            Spacing::Small => discord_types::SeparatorSpacing::Small,
+            Spacing::Large => discord_types::SeparatorSpacing::Large,
+        }
+    }
+}
+
+fn text_display(text: &str) -> Result<discord_types::Component, ValidationError> {
+    check_length("text display", text, 1, MAX_TOTAL_TEXT)?;
+
+    Ok(discord_types::Component {
+        content: Some(text.to_string()),
+        ..discord_types::Component::new(discord_types::ComponentType::TextDisplay)
+    })
+}
+
+fn unfurled(url: &str) -> discord_types::UnfurledMedia {
+    discord_types::UnfurledMedia {
+        url: url.to_string(),
+    }
+}
+
+fn media_description(media: &Media) -> Result<Option<String>, ValidationError> {
+    if let Some(description) = &media.description {
+        check_length("media description", description, 1, 1024)?;
+    }
+    Ok(media.description.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn component(value: serde_json::Value) -> discord_types::Component {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn sections_with_unknown_children_are_kept_whole() {
+        let section = json!({
+            "type": 9,
+            "components": [
+                { "type": 10, "content": "known" },
+                { "type": 99, "content": "unknown" },
+            ],
+            "accessory": { "type": 2, "style": 1, "custom_id": "b", "label": "B" },
+        });
+
+        assert_eq!(
+            parse_layout(&component(section.clone())),
+            LayoutComponent::Unknown(section)
+        );
+    }
+
+    #[test]
+    fn separators_with_unknown_spacing_are_kept_whole() {
+        let separator = json!({ "type": 14, "divider": true, "spacing": 3 });
+
+        assert_eq!(
+            parse_layout(&component(separator.clone())),
+            LayoutComponent::Unknown(separator)
+        );
+    }
+
+    #[test]
+    fn unknown_components_are_sent_back_unchanged() {
+        let unknown = json!({ "type": 99, "custom_id": "q", "fancy": true });
+        let parsed = parse_layout(&component(unknown.clone()));
+
+        let sent = discord_types::Component::try_from(&parsed).unwrap();
+
+        assert_eq!(serde_json::to_value(sent).unwrap(), unknown);
+    }
+}
@@ -6,9 +6,23 @@
 
 mod auth;
 mod discord_types;
+mod embed;
 mod handler;
+mod layout;
+mod multipart;
+mod poll;
+mod snowflake;
+mod suggest;
 mod user_types;
+mod watchdog;
+mod webhook;
 
-pub use auth::run;
+pub use auth::{run, run_with_config, Config};
+pub use embed::*;
 pub use handler::InteractionHandler;
+pub use layout::*;
+pub use poll::*;
+pub use snowflake::Snowflake;
+pub use suggest::suggest;
 pub use user_types::*;
+pub use webhook::*;
@@ -0,0 +1,66 @@
+/*!
+ * Encoding of `multipart/form-data` bodies, which Discord requires whenever a message is sent with
+ * files attached. The message itself goes in a `payload_json` part, followed by one part per file,
+ * named `files[n]` to match the ids in the message's `attachments` list.
+ */
+
+use super::discord_types::FileUpload;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Encodes a JSON payload and its files as a request or response body, returning the body along
+/// with its content type. Without files, the payload is sent as plain JSON.
+pub(crate) fn encode(payload: &serde_json::Value, files: &[FileUpload]) -> (String, Vec<u8>) {
+    if files.is_empty() {
+        return (
+            "application/json".to_string(),
+            payload.to_string().into_bytes(),
+        );
+    }
+
+    let boundary = boundary(files);
+    let mut body = Vec::new();
+
+    body.extend_from_slice(format!("--{boundary}\r\n").as_bytes());
+    body.extend_from_slice(b"Content-Disposition: form-data; name=\"payload_json\"\r\n");
+    body.extend_from_slice(b"Content-Type: application/json\r\n\r\n");
+    body.extend_from_slice(payload.to_string().as_bytes());
+    body.extend_from_slice(b"\r\n");
+
+    for (i, file) in files.iter().enumerate() {
+        let filename = file.filename.replace(['"', '\r', '\n'], "_");
+        body.extend_from_slice(format!("--{boundary}\r\n").as_bytes());
+        body.extend_from_slice(
+            format!(
+                "Content-Disposition: form-data; name=\"files[{i}]\"; filename=\"{filename}\"\r\n"
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(b"Content-Type: application/octet-stream\r\n\r\n");
+        body.extend_from_slice(&file.data);
+        body.extend_from_slice(b"\r\n");
+    }
+
+    body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+
+    (format!("multipart/form-data; boundary={boundary}"), body)
+}
+
+/// Picks a boundary that doesn't occur within any of the files.
+fn boundary(files: &[FileUpload]) -> String {
+    let mut seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64);
+
+    loop {
+        let boundary = format!("discord-interaction-{seed:016x}");
+        let occurs = files.iter().any(|file| {
+            file.data
+                .windows(boundary.len())
+                .any(|w| w == boundary.as_bytes())
+        });
+        if !occurs {
+            return boundary;
+        }
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+    }
+}
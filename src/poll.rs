@@ -0,0 +1,182 @@
+/*!
+ * Native polls, which let users vote on up to 10 answers to a question. A poll is attached to a
+ * message when it is sent, and its votes can be read back from the source message of a later
+ * interaction, such as a button that shows the current standings.
+ */
+
+use super::discord_types;
+use super::user_types::{check_count, check_length, Emoji, ValidationError};
+
+/// The longest that a poll can stay open, in hours.
+const MAX_DURATION_HOURS: u32 = 32 * 24;
+
+/// A poll, attached to a message.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Poll {
+    pub question: String,
+    pub answers: Vec<PollAnswer>,
+    /// How long the poll stays open, in hours. Defaults to 24 hours, and is at most 32 days.
+    pub duration_hours: u32,
+    /// If true, users can vote for more than one answer.
+    pub multiselect: bool,
+}
+
+/// An answer that users can vote for.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PollAnswer {
+    pub text: String,
+    pub emoji: Option<Emoji>,
+}
+
+/// A poll as found on a source message, along with its votes so far.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PollResults {
+    pub question: String,
+    pub answers: Vec<PollAnswerResults>,
+    /// When the poll closes, as an ISO 8601 timestamp.
+    pub expiry: Option<String>,
+    pub multiselect: bool,
+    /// If true, the poll has closed and its votes are final. Otherwise, Discord may have counted
+    /// the votes only approximately.
+    pub finalized: bool,
+}
+
+/// An answer of a poll, along with its votes so far.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PollAnswerResults {
+    pub id: u32,
+    pub text: Option<String>,
+    pub emoji: Option<Emoji>,
+    pub votes: u32,
+    /// If true, the user who triggered the interaction voted for this answer.
+    pub me_voted: bool,
+}
+
+/// Convenience methods for building polls.
+impl Poll {
+    /// Creates a new poll with the given question, open for 24 hours.
+    pub fn new(question: &str) -> Self {
+        Poll {
+            question: question.to_string(),
+            answers: Vec::new(),
+            duration_hours: 24,
+            multiselect: false,
+        }
+    }
+
+    /// Adds an answer to the poll. A poll has 1 to 10 answers.
+    pub fn answer(mut self, text: &str) -> Self {
+        self.answers.push(PollAnswer {
+            text: text.to_string(),
+            emoji: None,
+        });
+        self
+    }
+
+    /// Adds an answer, shown with an emoji, to the poll.
+    pub fn answer_with_emoji(mut self, text: &str, emoji: Emoji) -> Self {
+        self.answers.push(PollAnswer {
+            text: text.to_string(),
+            emoji: Some(emoji),
+        });
+        self
+    }
+
+    /// Sets how long the poll stays open, in hours.
+    pub fn duration_hours(mut self, hours: u32) -> Self {
+        self.duration_hours = hours;
+        self
+    }
+
+    /// Lets users vote for more than one answer.
+    pub fn multiselect(mut self) -> Self {
+        self.multiselect = true;
+        self
+    }
+}
+
+/// Convenience methods for reading poll results.
+impl PollResults {
+    /// Returns the total number of votes across all answers.
+    pub fn total_votes(&self) -> u32 {
+        self.answers.iter().map(|a| a.votes).sum()
+    }
+
+    /// Returns the answers with the most votes, of which there are several in case of a tie.
+    pub fn leaders(&self) -> Vec<&PollAnswerResults> {
+        let most = self.answers.iter().map(|a| a.votes).max().unwrap_or(0);
+        self.answers.iter().filter(|a| a.votes == most).collect()
+    }
+}
+
+impl TryFrom<Poll> for discord_types::PollCreateRequest {
+    type Error = ValidationError;
+
+    fn try_from(poll: Poll) -> Result<Self, Self::Error> {
+        check_length("poll question", &poll.question, 1, 300)?;
+        if poll.answers.is_empty() {
+            return Err(ValidationError::Invalid(
+                "a poll must have at least one answer",
+            ));
+        }
+        check_count("poll answers", poll.answers.len(), 10)?;
+        if !(1..=MAX_DURATION_HOURS).contains(&poll.duration_hours) {
+            return Err(ValidationError::Invalid(
+                "a poll must last between 1 hour and 32 days",
+            ));
+        }
+
+        let answers = poll
+            .answers
+            .into_iter()
+            .map(|answer| {
+                check_length("poll answer text", &answer.text, 1, 55)?;
+                Ok(discord_types::PollAnswer {
+                    answer_id: None,
+                    poll_media: discord_types::PollMedia {
+                        text: Some(answer.text),
+                        emoji: answer.emoji.map(|e| e.into()),
+                    },
+                })
+            })
+            .collect::<Result<_, ValidationError>>()?;
+
+        Ok(discord_types::PollCreateRequest {
+            question: discord_types::PollMedia {
+                text: Some(poll.question),
+                emoji: None,
+            },
+            answers,
+            duration: poll.duration_hours,
+            allow_multiselect: poll.multiselect,
+        })
+    }
+}
+
+impl From<&discord_types::Poll> for PollResults {
+    fn from(poll: &discord_types::Poll) -> Self {
+        let counts = poll.results.as_ref().map(|r| &r.answer_counts[..]);
+        let count = |id| counts.into_iter().flatten().find(|c| c.id == id);
+
+        PollResults {
+            question: poll.question.text.clone().unwrap_or_default(),
+            answers: poll
+                .answers
+                .iter()
+                .map(|answer| {
+                    let id = answer.answer_id.unwrap_or(0);
+                    PollAnswerResults {
+                        id,
+                        text: answer.poll_media.text.clone(),
+                        emoji: answer.poll_media.emoji.as_ref().map(|e| e.into()),
+                        votes: count(id).map_or(0, |c| c.count),
+                        me_voted: count(id).is_some_and(|c| c.me_voted),
+                    }
+                })
+                .collect(),
+            expiry: poll.expiry.clone(),
+            multiselect: poll.allow_multiselect,
+            finalized: poll.results.as_ref().is_some_and(|r| r.is_finalized),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+/*!
+ * Snowflakes, the ids that Discord gives to users, channels, messages, and everything else. A
+ * snowflake is a 64-bit integer that encodes when it was created, which Discord sends as a string
+ * so that JavaScript clients don't lose precision.
+ */
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The first millisecond of 2015, which snowflake timestamps count from, in milliseconds since the
+/// Unix epoch.
+const DISCORD_EPOCH_MS: u64 = 1_420_070_400_000;
+
+/// A Discord id. Snowflakes are ordered by when they were created.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Snowflake(u64);
+
+impl Snowflake {
+    /// Creates a snowflake from its integer value.
+    pub const fn new(id: u64) -> Self {
+        Snowflake(id)
+    }
+
+    /// Returns the integer value of the snowflake.
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+
+    /// Returns when the snowflake was created.
+    pub fn timestamp(self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis((self.0 >> 22) + DISCORD_EPOCH_MS)
+    }
+
+    /// Returns the id of the Discord worker that created the snowflake.
+    pub const fn worker_id(self) -> u8 {
+        ((self.0 >> 17) & 0x1F) as u8
+    }
+
+    /// Returns the id of the Discord process that created the snowflake.
+    pub const fn process_id(self) -> u8 {
+        ((self.0 >> 12) & 0x1F) as u8
+    }
+
+    /// Returns the number of snowflakes that the process had created before this one, wrapping
+    /// around every 4096.
+    pub const fn increment(self) -> u16 {
+        (self.0 & 0xFFF) as u16
+    }
+
+    /// Formats the snowflake as a mention of the user with this id, such as `<@80351110224678912>`.
+    pub fn user_mention(self) -> String {
+        format!("<@{}>", self.0)
+    }
+
+    /// Formats the snowflake as a mention of the role with this id.
+    pub fn role_mention(self) -> String {
+        format!("<@&{}>", self.0)
+    }
+
+    /// Formats the snowflake as a mention of the channel with this id.
+    pub fn channel_mention(self) -> String {
+        format!("<#{}>", self.0)
+    }
+}
+
+impl fmt::Display for Snowflake {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for Snowflake {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Snowflake)
+    }
+}
+
+impl From<u64> for Snowflake {
+    fn from(id: u64) -> Self {
+        Snowflake(id)
+    }
+}
+
+impl From<Snowflake> for u64 {
+    fn from(id: Snowflake) -> Self {
+        id.0
+    }
+}
+
+impl Serialize for Snowflake {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Snowflake {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(SnowflakeVisitor)
+    }
+}
+
+/// Reads a snowflake from Discord's string form, or from a plain integer.
+struct SnowflakeVisitor;
+
+impl Visitor<'_> for SnowflakeVisitor {
+    type Value = Snowflake;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a snowflake, as a string or an integer")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(Snowflake(v))
+    }
+}
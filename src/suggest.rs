@@ -0,0 +1,147 @@
+/*!
+ * Ranking of autocomplete choices against the partial input that the user has typed so far. This
+ * is meant to run on every keystroke, so it only allocates a little per candidate and skips the
+ * edit distance computation whenever a cheaper match is found.
+ */
+
+use super::user_types::Choice;
+
+/// The maximum number of choices Discord accepts in an autocomplete result.
+const MAX_CHOICES: usize = 25;
+
+/// Inputs shorter than this are only matched by prefix and substring, since almost any short
+/// string is within a typo or two of a candidate.
+const MIN_FUZZY_INPUT: usize = 3;
+
+/// The most typos that a fuzzy match can have, however long the input is. Each typo costs 100
+/// points of a fuzzy match's score of 500, so this keeps the score above zero.
+const MAX_TYPOS: usize = 4;
+
+/// Picks the (up to) 25 candidates that best match the user's partial input, best match first.
+/// Exact matches rank highest, followed by prefix matches, matches at the start of a word, and
+/// plain substring matches. If none of those apply, a candidate can still be suggested when the
+/// input is within a few typos of the start of its name. Matching is case-insensitive, and when a
+/// `locale` is given, a candidate's localized name for that locale is matched as well. If the input
+/// is empty, the first 25 candidates are returned in their original order.
+///
+/// This is typically called from `InteractionHandler::handle_autocomplete`:
+///
+/// ```ignore
+/// fn handle_autocomplete(ac: Autocomplete) -> Vec<Choice> {
+///     suggest(&FRUITS, &ac.input, ac.locale.as_deref())
+/// }
+/// ```
+pub fn suggest(candidates: &[Choice], input: &str, locale: Option<&str>) -> Vec<Choice> {
+    let input: Vec<char> = input.trim().to_lowercase().chars().collect();
+
+    if input.is_empty() {
+        return candidates.iter().take(MAX_CHOICES).cloned().collect();
+    }
+
+    let mut scored: Vec<(u32, usize, &Choice)> = candidates
+        .iter()
+        .filter_map(|choice| {
+            let localized = locale.and_then(|l| choice.name_localizations.get(l));
+            let best = std::iter::once(&choice.name)
+                .chain(localized)
+                .filter_map(|name| score(name, &input))
+                .max()?;
+            Some((best, choice.name.chars().count(), choice))
+        })
+        .collect();
+
+    // The sort is stable, so equally good matches keep their original order.
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+    scored
+        .into_iter()
+        .take(MAX_CHOICES)
+        .map(|(_, _, choice)| choice.clone())
+        .collect()
+}
+
+/// Scores how well `name` matches the lowercased `input`, or returns `None` if it doesn't match.
+fn score(name: &str, input: &[char]) -> Option<u32> {
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+
+    if let Some(pos) = find(&name, input) {
+        let extra = (name.len() - input.len()).min(999) as u32;
+
+        return Some(if pos == 0 && extra == 0 {
+            4000
+        } else if pos == 0 {
+            3000 - extra
+        } else if !name[pos - 1].is_alphanumeric() {
+            2000 - (pos.min(999) as u32)
+        } else {
+            1000 - (pos.min(999) as u32)
+        });
+    }
+
+    if input.len() < MIN_FUZZY_INPUT {
+        return None;
+    }
+
+    let max_distance = (input.len() / 3).clamp(1, MAX_TYPOS);
+    let distance = prefix_distance(input, &name, max_distance)?;
+    Some(500 - 100 * distance as u32)
+}
+
+/// Finds the first position at which `needle` occurs in `haystack`.
+fn find(haystack: &[char], needle: &[char]) -> Option<usize> {
+    if needle.len() > haystack.len() {
+        return None;
+    }
+
+    (0..=haystack.len() - needle.len()).find(|&i| haystack[i..].starts_with(needle))
+}
+
+/// Computes the smallest Levenshtein distance between `input` and any prefix of `name`, giving up
+/// and returning `None` as soon as it's certain to exceed `max_distance`.
+fn prefix_distance(input: &[char], name: &[char], max_distance: usize) -> Option<usize> {
+    let mut prev: Vec<usize> = (0..=name.len()).collect();
+    let mut curr = vec![0; name.len() + 1];
+
+    for (i, a) in input.iter().enumerate() {
+        curr[0] = i + 1;
+        for (j, b) in name.iter().enumerate() {
+            let substitution = prev[j] + usize::from(a != b);
+            curr[j + 1] = substitution.min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+
+        if curr.iter().min()? > &max_distance {
+            return None;
+        }
+
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    prev.into_iter().min().filter(|&d| d <= max_distance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_inputs_with_many_typos_do_not_outrank_exact_matches() {
+        let candidates = vec![
+            Choice::new("abcdefghijklmnopqrstuvwx", "fuzzy"),
+            Choice::new("zzzzzzghijklmnopqr", "exact"),
+        ];
+
+        let suggestions = suggest(&candidates, "zzzzzzghijklmnopqr", None);
+
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].name, "zzzzzzghijklmnopqr");
+    }
+
+    #[test]
+    fn long_inputs_still_match_with_a_few_typos() {
+        let candidates = vec![Choice::new("abcdefghijklmnopqrstuvwx", "fuzzy")];
+
+        let suggestions = suggest(&candidates, "abcdefghijklmnopqrzz", None);
+
+        assert_eq!(suggestions.len(), 1);
+    }
+}
@@ -1,58 +1,465 @@
 use super::discord_types;
+use super::embed::{embeds_into_wire, Embed};
+use super::layout::{layout_into_wire, parse_layout, LayoutComponent};
+use super::poll::{Poll, PollResults};
+use super::snowflake::Snowflake;
+use super::webhook::{strip_ephemeral, API_BASE_URL};
 use std::collections::HashMap;
+use std::fmt;
+use std::ops::{BitOr, BitOrAssign};
+use std::time::{Duration, SystemTime};
 
 /// An top level interaction initiated by the user. Application commands do not require any existing conversation with the bot to be occurring. Currently, only chat application commands (slash commands) are fully supported.
 pub struct ApplicationCommand {
     pub command_name: String,
-    pub user_id: String,
+    pub user_id: Snowflake,
+    /// The token used to follow up on this interaction after responding to it.
+    pub token: InteractionToken,
+    /// The interaction as sent by Discord, for reading fields that this library doesn't model.
+    pub raw: serde_json::Value,
 }
 
-/// An interaction caused by the user's interaction with a message component embedded in a chat message. Currently, only button presses are supported.
+/// An interaction sent while the user is typing into an application command option that has
+/// autocomplete enabled. The application responds with a list of suggested choices.
+pub struct Autocomplete {
+    pub command_name: String,
+    pub user_id: Snowflake,
+    /// The name of the option currently being typed into.
+    pub option: String,
+    /// The partial input the user has typed into the focused option so far.
+    pub input: String,
+    /// The locale of the user, such as `en-US`, if Discord provided one.
+    pub locale: Option<String>,
+    /// The interaction as sent by Discord, for reading fields that this library doesn't model.
+    pub raw: serde_json::Value,
+}
+
+/// An interaction caused by the user's interaction with a message component embedded in a chat message. Currently, button presses and select menu choices are supported.
 pub struct MessageComponent {
     pub id: String,
 
+    /// The id of the user who used the component.
+    pub user_id: Snowflake,
+
+    /// The values of the options that the user picked, if the component is a select menu. For
+    /// user, role, mentionable and channel select menus, these are the ids of the picked items.
+    pub values: Vec<String>,
+
+    /// The users, roles and channels picked, if the component is an auto-populated select menu.
+    pub resolved: Resolved,
+
+    /// The token used to follow up on this interaction after responding to it.
+    pub token: InteractionToken,
+
     /// The message that this component was originally attached to.
     pub source: SourceMessage,
+
+    /// The interaction as sent by Discord, for reading fields that this library doesn't model.
+    pub raw: serde_json::Value,
 }
 
 /// An interaction type caused by the user submitting a completed modal form. Modals are the
 /// primary way of retrieving text input from the user.
 pub struct ModalSubmit {
     pub id: String,
+    /// The submitted text of each field, keyed by field id. An optional field that the user left
+    /// empty has an empty value.
     pub values: HashMap<String, String>,
-    /// The message that this modal was originally attached to.
-    pub source: SourceMessage,
+    /// The token used to follow up on this interaction after responding to it.
+    pub token: InteractionToken,
+    /// Where the modal was opened from.
+    pub origin: ModalOrigin,
+    /// The message holding the component that opened the modal. A modal opened directly from an
+    /// application command has no source message, so its submission must be answered with a new
+    /// message rather than an edit.
+    pub source: Option<SourceMessage>,
+    /// The interaction as sent by Discord, for reading fields that this library doesn't model.
+    pub raw: serde_json::Value,
+}
+
+/// The interaction that a modal was opened in response to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ModalOrigin {
+    ApplicationCommand,
+    MessageComponent,
 }
 
 /// A message that a message component or modal was originally attached to. This allows the
 /// application to maintain some notion of "state", by reasoning based on the source message's
-/// text.
+/// text and the components that it currently shows.
 pub struct SourceMessage {
+    pub id: Snowflake,
+    pub channel_id: Snowflake,
+    /// The user who sent the message, which is the application itself for its own responses.
+    pub author: User,
+    /// When the message was sent, as an ISO 8601 timestamp.
+    pub timestamp: String,
+    /// When the message was last edited, as an ISO 8601 timestamp.
+    pub edited_timestamp: Option<String>,
     pub text: String,
+    pub embeds: Vec<Embed>,
+    /// The rows of components shown below the message's text. Components that this library
+    /// doesn't know are kept as `RowComponent::Unknown`.
+    pub rows: Vec<ActionRow>,
+    /// The layout components of a message laid out with Components V2. Components that this
+    /// library doesn't know are kept as `LayoutComponent::Unknown`.
+    pub layout: Vec<LayoutComponent>,
+    pub flags: MessageFlags,
+    /// The poll attached to the message, along with its votes so far.
+    pub poll: Option<PollResults>,
+    /// The interaction that the message responds to, if it is an interaction response. This tells
+    /// who invoked the original command.
+    pub interaction_metadata: Option<InteractionMetadata>,
+    /// If true, the message's components only respond to the user who invoked the original command.
+    pub invoker_only: bool,
+}
+
+/// Information about the interaction that a message was sent in response to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InteractionMetadata {
+    pub id: Snowflake,
+    pub kind: InteractionKind,
+    /// The user who triggered the interaction.
+    pub user: User,
+    /// The id of the original response, if this message is a follow-up.
+    pub original_response_message_id: Option<Snowflake>,
+    /// The id of the message holding the component that triggered the interaction, if any.
+    pub interacted_message_id: Option<Snowflake>,
+}
+
+/// The kind of an interaction.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InteractionKind {
+    Ping,
+    ApplicationCommand,
+    MessageComponent,
+    Autocomplete,
+    ModalSubmit,
+    /// An interaction type that this library does not know about yet.
+    Unknown(u8),
+}
+
+/// The credentials needed to keep responding to an interaction through its webhook, once the
+/// initial response has been sent. Discord keeps an interaction token valid for 15 minutes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InteractionToken {
+    pub interaction_id: Snowflake,
+    pub application_id: Snowflake,
+    pub token: String,
 }
 
 /// A response to an interaction. This response can either be a message in chat, or a modal, which
-/// will pop up over the user's screen.
+/// will pop up over the user's screen. If the application needs longer than Discord's 3 second
+/// window to respond, it can defer its response instead, and complete it later through the
+/// interaction webhook.
 pub enum Response {
     Message(Message),
     Modal(Modal),
+    /// Shows a loading state in chat, to be replaced by a message later. If `ephemeral` is true,
+    /// the eventual message will be visible to only the recipient.
+    Defer {
+        ephemeral: bool,
+    },
+    /// Acknowledges a message component or modal submit without changing the source message yet,
+    /// so that it can be edited later.
+    DeferUpdate,
+    /// Acknowledges a message component or modal submit without any visible change, such as for
+    /// a button that only has side effects. The `followups`, if any, are sent as new messages once
+    /// the interaction has been acknowledged.
+    Acknowledge {
+        followups: Vec<Message>,
+    },
+    /// A response given as raw JSON, which is sent as is, without being checked against Discord's
+    /// limits. This allows using features of Discord's API that this library doesn't model yet.
+    Raw(serde_json::Value),
+}
+
+/// A suggested value for an autocompleted option. The `name` is shown to the user, and the `value`
+/// is what the application receives once the user picks the choice.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Choice {
+    pub name: String,
+    pub value: ChoiceValue,
+    /// Names to show in place of `name` for users with the given locales, keyed by locale.
+    pub name_localizations: HashMap<String, String>,
+}
+
+/// The value of an autocomplete choice, which must match the type of the option being completed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChoiceValue {
+    String(String),
+    Integer(i64),
+    Number(f64),
+}
+
+/// An error raised when an interaction request from Discord lacks something that this library
+/// needs in order to convert it for the handler.
+#[derive(Debug, PartialEq)]
+pub enum ConversionError {
+    /// A field that the interaction requires is missing, given by its path in the request.
+    MissingField(&'static str),
+    /// The interaction is of a type that this library doesn't know.
+    UnknownType(u8),
+}
+
+/// An error raised when a response breaks one of Discord's limits, such as the maximum number of
+/// choices or the maximum length of a label.
+#[derive(Debug, PartialEq)]
+pub enum ValidationError {
+    /// More items were provided than Discord allows.
+    TooMany {
+        what: &'static str,
+        max: usize,
+        found: usize,
+    },
+    /// A string's length falls outside of the range that Discord allows.
+    Length {
+        what: &'static str,
+        min: usize,
+        max: usize,
+        found: usize,
+    },
+    /// The response is not allowed for the interaction that it responds to.
+    Unsupported(&'static str),
+    /// A component is missing a field that it requires, or has one that it cannot have.
+    Invalid(&'static str),
 }
 
 /// A message response, resulting in a message in chat.
 pub struct Message {
     pub text: String,
-    pub buttons: Vec<Button>,
-    /// If true, the message will be visible to only the recipient.
-    pub ephemeral: bool,
+    /// The rows of components shown below the message's text. A message has at most 5 rows.
+    pub rows: Vec<ActionRow>,
+    /// Rich embeds shown below the message's text. A message has at most 10 embeds.
+    pub embeds: Vec<Embed>,
+    /// Layout components, shown in place of text and embeds. If the message has a layout, its
+    /// rows are shown below it, and it cannot have text or embeds.
+    pub layout: Vec<LayoutComponent>,
+    /// Flags changing how the message is shown, such as whether it is ephemeral.
+    pub flags: MessageFlags,
+    /// Which mentions in the message notify the users they mention.
+    pub allowed_mentions: AllowedMentions,
+    /// If true, the message's components only respond to the user that the message responds to.
+    pub invoker_only: bool,
+    /// Files uploaded with the message. A message has at most 10 attachments.
+    pub attachments: Vec<Attachment>,
+    /// A poll attached to the message. A poll can only be attached to a new message.
+    pub poll: Option<Poll>,
     /// If true, the message will replace the original message.
     pub edit: bool,
 }
 
+/// A set of flags that change how a message is shown. Flags are combined with `|`, such as
+/// `MessageFlags::EPHEMERAL | MessageFlags::SUPPRESS_NOTIFICATIONS`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct MessageFlags(u32);
+
+/// A file uploaded with a message. Embeds and layout components in the same message can show the
+/// file by referring to it as `attachment://<filename>`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Attachment {
+    pub filename: String,
+    pub data: Vec<u8>,
+    /// Alt text for the file.
+    pub description: Option<String>,
+}
+
+/// Controls which mentions in a message actually notify anyone. Mentions that aren't allowed are
+/// still shown, but nobody is pinged. By default, users can be mentioned, but roles, `@everyone`,
+/// and `@here` cannot, so that text echoed from user input can't ping a whole guild.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AllowedMentions {
+    /// Whether `@everyone` and `@here` notify anyone.
+    pub everyone: bool,
+    pub users: MentionPolicy,
+    pub roles: MentionPolicy,
+    /// Whether the author of the message being replied to is notified.
+    pub replied_user: bool,
+}
+
+/// Which users or roles can be mentioned by a message.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MentionPolicy {
+    /// Any user or role mentioned in the message.
+    All,
+    /// Only the users or roles with the listed ids, up to 100. An empty list allows none.
+    Only(Vec<Snowflake>),
+}
+
+/// A row of components in a message. A row holds either up to 5 buttons, or a single select menu.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActionRow {
+    pub components: Vec<RowComponent>,
+}
+
+/// A component that can be placed in an action row.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RowComponent {
+    Button(Button),
+    Select(SelectMenu),
+    /// A component of a received message that this library doesn't know, such as one that
+    /// Discord added later. It is kept as raw JSON, and sent back unchanged.
+    Unknown(serde_json::Value),
+}
+
 /// A button component, which the user can interact with. If a user clicks such
-/// a button, it will spawn a message component interaction.
+/// a button, it will spawn a message component interaction, unless it is a link button, which
+/// opens its URL instead, or a premium button, which prompts the user to purchase its SKU.
+#[derive(Clone, Debug, PartialEq)]
 pub struct Button {
-    pub id: String,
+    /// The id sent with the interaction when the button is clicked. Link and premium buttons
+    /// don't spawn interactions, so they have no id.
+    pub id: Option<String>,
     pub text: String,
+    pub style: ButtonStyle,
+    pub emoji: Option<Emoji>,
+    /// The URL opened by a link button.
+    pub url: Option<String>,
+    /// The id of the SKU offered by a premium button.
+    pub sku_id: Option<Snowflake>,
+    /// If true, the button is shown grayed out, and cannot be clicked.
+    pub disabled: bool,
+}
+
+/// The appearance and behavior of a button.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ButtonStyle {
+    /// A blurple button.
+    Primary,
+    /// A gray button.
+    Secondary,
+    /// A green button.
+    Success,
+    /// A red button.
+    Danger,
+    /// A gray button that navigates to a URL.
+    Link,
+    /// A button that prompts the user to purchase a SKU.
+    Premium,
+}
+
+/// A select menu component, which lets the user pick one or more options from a dropdown. Once the
+/// user has picked, it will spawn a message component interaction holding the chosen values.
+/// Besides menus of custom options, Discord can populate menus with users, roles, mentionables
+/// (users and roles), or channels.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SelectMenu {
+    pub id: String,
+    pub kind: SelectKind,
+    /// The options to pick from. Only string select menus have options.
+    pub options: Vec<SelectOption>,
+    /// The types of channels that a channel select menu offers. If empty, all types are offered.
+    pub channel_types: Vec<ChannelType>,
+    /// The items that an auto-populated select menu shows as picked when it is first shown.
+    pub default_values: Vec<SelectDefault>,
+    /// The text shown when nothing is picked.
+    pub placeholder: Option<String>,
+    /// The minimum number of options that must be picked.
+    pub min_values: u8,
+    /// The maximum number of options that can be picked.
+    pub max_values: u8,
+    /// If true, the select menu is shown grayed out, and cannot be used.
+    pub disabled: bool,
+}
+
+/// What a select menu lets the user pick from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SelectKind {
+    /// Options defined by the application.
+    String,
+    User,
+    Role,
+    /// Users and roles.
+    Mentionable,
+    Channel,
+}
+
+/// An item picked by default in an auto-populated select menu, given by its id.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SelectDefault {
+    User(Snowflake),
+    Role(Snowflake),
+    Channel(Snowflake),
+}
+
+/// The type of a Discord channel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChannelType {
+    Text,
+    Dm,
+    Voice,
+    GroupDm,
+    Category,
+    Announcement,
+    AnnouncementThread,
+    PublicThread,
+    PrivateThread,
+    StageVoice,
+    Directory,
+    Forum,
+    Media,
+    /// A channel type that this library does not know about yet.
+    Unknown(u8),
+}
+
+/// The users, roles and channels picked in an auto-populated select menu, in the order that their
+/// ids appear in the interaction's values.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Resolved {
+    pub users: Vec<User>,
+    pub roles: Vec<Role>,
+    pub channels: Vec<Channel>,
+}
+
+/// A Discord user.
+#[derive(Clone, Debug, PartialEq)]
+pub struct User {
+    pub id: Snowflake,
+    pub username: String,
+    /// The user's display name, if it differs from their username.
+    pub global_name: Option<String>,
+    /// The user's nickname in the guild, if they have one there.
+    pub nick: Option<String>,
+    pub bot: bool,
+}
+
+/// A role in a guild.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Role {
+    pub id: Snowflake,
+    pub name: String,
+    /// The role's color, as an RGB integer.
+    pub color: u32,
+}
+
+/// A channel, as seen by a select menu.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Channel {
+    pub id: Snowflake,
+    pub name: Option<String>,
+    pub kind: ChannelType,
+    /// The category holding the channel, or the channel holding the thread.
+    pub parent_id: Option<Snowflake>,
+}
+
+/// An option in a select menu.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SelectOption {
+    pub label: String,
+    /// The value sent with the interaction when the option is picked.
+    pub value: String,
+    pub description: Option<String>,
+    pub emoji: Option<Emoji>,
+    /// If true, the option is picked when the menu is first shown.
+    pub selected: bool,
+}
+
+/// An emoji shown on a component. This is either a unicode emoji, or a custom emoji from a guild.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Emoji {
+    pub id: Option<Snowflake>,
+    pub name: Option<String>,
+    pub animated: bool,
 }
 
 /// A modal response, which allows the user to input text information. A modal cannot be a response
@@ -67,6 +474,179 @@ pub struct Modal {
 pub struct TextField {
     pub id: String,
     pub label: String,
+    pub style: TextInputStyle,
+    /// The minimum number of characters the user must enter.
+    pub min_length: Option<u16>,
+    /// The maximum number of characters the user may enter.
+    pub max_length: Option<u16>,
+    /// If false, the user may submit the modal with this field left empty.
+    pub required: bool,
+    /// The text shown when the field is empty.
+    pub placeholder: Option<String>,
+    /// Text to prefill the field with, such as the current value of something being edited.
+    pub value: Option<String>,
+}
+
+/// The size of a text field.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextInputStyle {
+    /// A single line of text.
+    Short,
+    /// Multiple lines of text.
+    Paragraph,
+}
+
+/// The prefix marking the custom id of a component that only responds to the invoking user.
+const INVOKER_ONLY_PREFIX: &str = "~owner~";
+
+/// How long Discord keeps an interaction token valid for.
+const TOKEN_LIFETIME: Duration = Duration::from_secs(15 * 60);
+
+impl InteractionToken {
+    /// The webhook URL of the original response to this interaction. A deferred response is
+    /// completed by sending a `PATCH` request to this URL, with a message's
+    /// [`Message::into_webhook_json`] as the body.
+    pub fn original_response_url(&self) -> String {
+        self.webhook_url(API_BASE_URL, "/messages/@original")
+    }
+
+    /// The URL of this interaction's webhook on the given API, followed by `path`.
+    pub(crate) fn webhook_url(&self, base_url: &str, path: &str) -> String {
+        format!(
+            "{}/webhooks/{}/{}{}",
+            base_url, self.application_id, self.token, path
+        )
+    }
+
+    /// The time at which the token stops being accepted by Discord, which is 15 minutes after the
+    /// interaction was created.
+    pub fn expires_at(&self) -> SystemTime {
+        self.interaction_id.timestamp() + TOKEN_LIFETIME
+    }
+
+    /// Returns true if the token can no longer be used.
+    pub fn is_expired(&self) -> bool {
+        SystemTime::now() >= self.expires_at()
+    }
+}
+
+/// Convenience methods for building choices.
+impl Choice {
+    /// Creates a new choice with the given display name and value.
+    pub fn new(name: &str, value: impl Into<ChoiceValue>) -> Self {
+        Choice {
+            name: name.to_string(),
+            value: value.into(),
+            name_localizations: HashMap::new(),
+        }
+    }
+
+    /// Sets the name shown to users with the given locale.
+    pub fn localized(mut self, locale: &str, name: &str) -> Self {
+        self.name_localizations
+            .insert(locale.to_string(), name.to_string());
+        self
+    }
+}
+
+impl From<&str> for ChoiceValue {
+    fn from(value: &str) -> Self {
+        ChoiceValue::String(value.to_string())
+    }
+}
+
+impl From<String> for ChoiceValue {
+    fn from(value: String) -> Self {
+        ChoiceValue::String(value)
+    }
+}
+
+impl From<i64> for ChoiceValue {
+    fn from(value: i64) -> Self {
+        ChoiceValue::Integer(value)
+    }
+}
+
+impl From<f64> for ChoiceValue {
+    fn from(value: f64) -> Self {
+        ChoiceValue::Number(value)
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::TooMany { what, max, found } => {
+                write!(
+                    f,
+                    "too many {what}: found {found}, but at most {max} are allowed"
+                )
+            }
+            ValidationError::Length {
+                what,
+                min,
+                max,
+                found,
+            } => write!(
+                f,
+                "{what} must be between {min} and {max} characters long, but is {found}"
+            ),
+            ValidationError::Unsupported(reason) => write!(f, "unsupported response: {reason}"),
+            ValidationError::Invalid(reason) => write!(f, "invalid component: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConversionError::MissingField(field) => {
+                write!(f, "interaction request is missing `{field}`")
+            }
+            ConversionError::UnknownType(t) => write!(f, "unknown interaction type {t}"),
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+/// Checks that a string's length, in characters, falls within Discord's limits.
+pub(crate) fn check_length(
+    what: &'static str,
+    s: &str,
+    min: usize,
+    max: usize,
+) -> Result<(), ValidationError> {
+    let found = s.chars().count();
+    if found < min || found > max {
+        return Err(ValidationError::Length {
+            what,
+            min,
+            max,
+            found,
+        });
+    }
+    Ok(())
+}
+
+/// Checks that a list holds no more items than Discord allows.
+pub(crate) fn check_count(
+    what: &'static str,
+    found: usize,
+    max: usize,
+) -> Result<(), ValidationError> {
+    if found > max {
+        return Err(ValidationError::TooMany { what, max, found });
+    }
+    Ok(())
+}
+
+impl Default for Message {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 /// Convenience methods for building messages.
@@ -75,8 +655,14 @@ impl Message {
     pub fn new() -> Self {
         Message {
             text: "".to_string(),
-            buttons: Vec::new(),
-            ephemeral: false,
+            rows: Vec::new(),
+            embeds: Vec::new(),
+            layout: Vec::new(),
+            flags: MessageFlags::empty(),
+            allowed_mentions: AllowedMentions::default(),
+            invoker_only: false,
+            attachments: Vec::new(),
+            poll: None,
             edit: false,
         }
     }
@@ -87,18 +673,108 @@ impl Message {
         self
     }
 
-    /// Adds a button to the message.
-    pub fn button(mut self, id: &str, text: &str) -> Self {
-        self.buttons.push(Button {
-            id: id.to_string(),
-            text: text.to_string(),
-        });
+    /// Adds a button to the message. The button joins the last row if that row only holds
+    /// buttons and has room for another, and starts a new row otherwise.
+    pub fn button(self, id: &str, text: &str) -> Self {
+        self.button_with(Button::new(id, text))
+    }
+
+    /// Adds a link button, which opens the given URL when clicked, to the message.
+    pub fn link_button(self, url: &str, text: &str) -> Self {
+        self.button_with(Button::link(url, text))
+    }
+
+    /// Adds a premium button, which prompts the user to purchase the given SKU, to the message.
+    pub fn premium_button(self, sku_id: Snowflake) -> Self {
+        self.button_with(Button::premium(sku_id))
+    }
+
+    /// Adds a fully customized button to the message, placed like `button`.
+    pub fn button_with(mut self, button: Button) -> Self {
+        match self.rows.last_mut() {
+            Some(row) if row.has_room_for_button() => {
+                row.components.push(RowComponent::Button(button))
+            }
+            _ => self.rows.push(ActionRow::new().button_with(button)),
+        }
+        self
+    }
+
+    /// Adds a select menu to the message, in a row of its own.
+    pub fn select(mut self, select: SelectMenu) -> Self {
+        self.rows.push(ActionRow::new().select(select));
+        self
+    }
+
+    /// Adds a row of components to the message, for explicit control over how they are laid out.
+    pub fn row(mut self, row: ActionRow) -> Self {
+        self.rows.push(row);
+        self
+    }
+
+    /// Adds an embed to the message.
+    pub fn embed(mut self, embed: Embed) -> Self {
+        self.embeds.push(embed);
+        self
+    }
+
+    /// Adds a layout component, such as a section or a container, to the message. A message with
+    /// a layout cannot also have text or embeds.
+    pub fn layout(mut self, component: impl Into<LayoutComponent>) -> Self {
+        self.layout.push(component.into());
         self
     }
 
     /// Sets the message to be ephemeral.
-    pub fn ephemeral(mut self) -> Self {
-        self.ephemeral = true;
+    pub fn ephemeral(self) -> Self {
+        self.flags(MessageFlags::EPHEMERAL)
+    }
+
+    /// Hides the embeds that Discord would otherwise generate for links in the message's text.
+    pub fn suppress_embeds(self) -> Self {
+        self.flags(MessageFlags::SUPPRESS_EMBEDS)
+    }
+
+    /// Sends the message without notifying the users that it mentions.
+    pub fn suppress_notifications(self) -> Self {
+        self.flags(MessageFlags::SUPPRESS_NOTIFICATIONS)
+    }
+
+    /// Adds the given flags to the message.
+    pub fn flags(mut self, flags: MessageFlags) -> Self {
+        self.flags |= flags;
+        self
+    }
+
+    /// Attaches a file to the message, which embeds and layout components can show by referring to
+    /// it as `attachment://<filename>`. An empty description is left out.
+    pub fn attach(mut self, filename: &str, data: impl Into<Vec<u8>>, description: &str) -> Self {
+        self.attachments.push(Attachment {
+            filename: filename.to_string(),
+            data: data.into(),
+            description: (!description.is_empty()).then(|| description.to_string()),
+        });
+        self
+    }
+
+    /// Restricts the message's components to the user that the message responds to, such as the
+    /// user who invoked the original command. Anyone else who uses them gets the ephemeral reply of
+    /// `InteractionHandler::handle_foreign_component`, without the component's handler being
+    /// called. This marks the custom ids of the components, which leaves them 93 characters.
+    pub fn invoker_only(mut self) -> Self {
+        self.invoker_only = true;
+        self
+    }
+
+    /// Attaches a poll to the message.
+    pub fn poll(mut self, poll: Poll) -> Self {
+        self.poll = Some(poll);
+        self
+    }
+
+    /// Sets which mentions in the message notify anyone, in place of the safe default.
+    pub fn allowed_mentions(mut self, allowed_mentions: AllowedMentions) -> Self {
+        self.allowed_mentions = allowed_mentions;
         self
     }
 
@@ -107,188 +783,1830 @@ impl Message {
         self.edit = true;
         self
     }
-}
 
-/// Convenience methods for building modals.
-impl Modal {
-    /// Creates a new modal.
-    pub fn new() -> Self {
-        Modal {
-            id: "unknown modal".to_string(),
-            title: "".to_string(),
-            fields: Vec::new(),
+    /// Creates an edit of the source message, starting from its current text, embeds, and
+    /// components, so that a handler only needs to change what differs. The message's existing
+    /// attachments are kept, and its poll, which cannot be edited, is left as is.
+    pub fn from_source(source: &SourceMessage) -> Self {
+        let flags = MessageFlags::from_bits(source.flags.bits() & SETTABLE_FLAGS.bits());
+
+        Message {
+            text: source.text.clone(),
+            rows: source.rows.clone(),
+            embeds: source.embeds.clone(),
+            layout: source.layout.clone(),
+            flags,
+            invoker_only: source.invoker_only,
+            ..Message::new()
         }
+        .edit()
     }
 
-    /// Sets the `id` of the modal.
-    pub fn id(mut self, id: &str) -> Self {
-        self.id = id.to_string();
+    /// Replaces the button with the given id by the result of `f`, such as to change its text or
+    /// style. Buttons in the message's layout are included.
+    pub fn update_button(mut self, id: &str, f: impl FnOnce(Button) -> Button) -> Self {
+        let mut f = Some(f);
+        self.for_each_button(&mut |button| {
+            if button.id.as_deref() == Some(id) {
+                if let Some(f) = f.take() {
+                    *button = f(button.clone());
+                }
+            }
+        });
         self
     }
 
-    /// Sets the `title` of the modal.
-    pub fn title(mut self, title: &str) -> Self {
-        self.title = title.to_string();
+    /// Replaces the select menu with the given id by the result of `f`, such as to change which of
+    /// its options are picked. Select menus in the message's layout are included.
+    pub fn update_select(mut self, id: &str, f: impl FnOnce(SelectMenu) -> SelectMenu) -> Self {
+        let mut f = Some(f);
+        self.for_each_select(&mut |select| {
+            if select.id == id {
+                if let Some(f) = f.take() {
+                    *select = f(select.clone());
+                }
+            }
+        });
         self
     }
 
-    /// Adds a field to the modal.
-    pub fn field(mut self, id: &str, label: &str) -> Self {
-        self.fields.push(TextField {
-            id: id.to_string(),
-            label: label.to_string(),
-        });
+    /// Replaces the embed at the given index by the result of `f`, such as to change one of its
+    /// fields. Nothing happens if the message has no embed at that index.
+    pub fn update_embed(mut self, index: usize, f: impl FnOnce(Embed) -> Embed) -> Self {
+        if let Some(embed) = self.embeds.get_mut(index) {
+            *embed = f(embed.clone());
+        }
         self
     }
-}
 
-impl From<&discord_types::InteractionRequest> for ApplicationCommand {
-    fn from(req: &discord_types::InteractionRequest) -> Self {
-        ApplicationCommand {
-            command_name: req.data.as_ref().unwrap().name.as_ref().unwrap().clone(),
-            user_id: req.member.as_ref().unwrap().user.id.clone(),
-        }
+    /// Disables every button and select menu in the message, such as once a choice was made.
+    pub fn disable_all(mut self) -> Self {
+        self.for_each_button(&mut |button| button.disabled = true);
+        self.for_each_select(&mut |select| select.disabled = true);
+        self
     }
-}
 
-impl From<&discord_types::InteractionRequest> for MessageComponent {
-    fn from(req: &discord_types::InteractionRequest) -> Self {
-        MessageComponent {
-            id: req
-                .data
-                .as_ref()
-                .unwrap()
-                .custom_id
-                .as_ref()
-                .unwrap()
-                .clone(),
+    fn for_each_button(&mut self, f: &mut dyn FnMut(&mut Button)) {
+        for row in &mut self.rows {
+            row.for_each_button(f);
+        }
+        for component in &mut self.layout {
+            component.for_each_button(f);
+        }
+    }
 
-            source: req.message.as_ref().unwrap().into(),
+    fn for_each_select(&mut self, f: &mut dyn FnMut(&mut SelectMenu)) {
+        for row in &mut self.rows {
+            row.for_each_select(f);
+        }
+        for component in &mut self.layout {
+            component.for_each_select(f);
         }
     }
+
+    /// Converts the message into a JSON body for the interaction webhook, such as for completing
+    /// a deferred response. Whether the message is ephemeral or an edit was already decided by the
+    /// initial response, so those settings are not included. Attached files must be uploaded as
+    /// parts of a multipart body, which `WebhookClient` takes care of, so their contents are not
+    /// included either.
+    pub fn into_webhook_json(self) -> Result<serde_json::Value, ValidationError> {
+        let mut data = discord_types::InteractionCallbackData::try_from(self)?;
+        data.flags = strip_ephemeral(data.flags);
+        Ok(serde_json::json!(data))
+    }
 }
 
-impl From<&discord_types::InteractionRequest> for ModalSubmit {
-    fn from(req: &discord_types::InteractionRequest) -> Self {
-        ModalSubmit {
-            id: req
-                .data
-                .as_ref()
-                .unwrap()
-                .custom_id
-                .as_ref()
-                .unwrap()
-                .clone(),
+/// Methods for working with message flags.
+/// The flags that a response can set. Discord sets the others itself, such as on messages that
+/// have a thread, so they are dropped when a source message is edited.
+const SETTABLE_FLAGS: MessageFlags = MessageFlags::from_bits(
+    MessageFlags::SUPPRESS_EMBEDS.bits()
+        | MessageFlags::SUPPRESS_NOTIFICATIONS.bits()
+        | MessageFlags::IS_COMPONENTS_V2.bits(),
+);
 
-            values: req
-                .data
-                .as_ref()
-                .unwrap()
-                .components
-                .as_ref()
-                .unwrap()
-                .iter()
-                .map(|row| {
-                    let inner = &row.components.as_ref().unwrap()[0];
-                    (
-                        inner.custom_id.as_ref().unwrap().clone(),
-                        inner.value.as_ref().unwrap().clone(),
-                    )
-                })
-                .collect(),
+impl MessageFlags {
+    /// The message is visible to only the recipient.
+    pub const EPHEMERAL: MessageFlags = MessageFlags(1 << 6);
+    /// Discord does not generate embeds for links in the message's text.
+    pub const SUPPRESS_EMBEDS: MessageFlags = MessageFlags(1 << 2);
+    /// Users mentioned by the message are not notified.
+    pub const SUPPRESS_NOTIFICATIONS: MessageFlags = MessageFlags(1 << 12);
+    /// The message is laid out with layout components, in place of text and embeds. This is set
+    /// automatically on messages with a layout.
+    pub const IS_COMPONENTS_V2: MessageFlags = MessageFlags(1 << 15);
 
-            source: req.message.as_ref().unwrap().into(),
-        }
+    /// Creates an empty set of flags.
+    pub const fn empty() -> Self {
+        MessageFlags(0)
+    }
+
+    /// Creates a set of flags from their raw bits, keeping any bits that this library doesn't know.
+    pub const fn from_bits(bits: u32) -> Self {
+        MessageFlags(bits)
+    }
+
+    /// Returns the raw bits of the flags.
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Checks whether every flag in `other` is also set in `self`.
+    pub const fn contains(self, other: MessageFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Checks whether no flags are set.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Sets the given flags.
+    pub fn insert(&mut self, other: MessageFlags) {
+        self.0 |= other.0;
+    }
+
+    /// Clears the given flags.
+    pub fn remove(&mut self, other: MessageFlags) {
+        self.0 &= !other.0;
     }
 }
 
-impl From<&discord_types::Message> for SourceMessage {
-    fn from(msg: &discord_types::Message) -> Self {
-        SourceMessage {
-            text: msg.content.clone(),
+impl Default for AllowedMentions {
+    fn default() -> Self {
+        AllowedMentions {
+            everyone: false,
+            users: MentionPolicy::All,
+            roles: MentionPolicy::Only(Vec::new()),
+            replied_user: false,
         }
     }
 }
 
-impl Into<discord_types::InteractionResponse> for Message {
-    fn into(self) -> discord_types::InteractionResponse {
-        let rows = self
-            .buttons
-            .chunks(5)
-            .map(|chunk| discord_types::Component {
-                r#type: discord_types::ComponentType::ActionRow,
-                label: None,
-                style: None,
-                custom_id: None,
-                value: None,
-                components: Some(chunk.iter().map(|b| b.into()).collect()),
-            })
-            .collect();
-
-        discord_types::InteractionResponse {
-            r#type: if self.edit {
-                discord_types::InteractionCallbackType::UpdateMessage
-            } else {
-                discord_types::InteractionCallbackType::ChannelMessageWithSource
-            },
+/// Convenience methods for building allowed mentions.
+impl AllowedMentions {
+    /// Allows no mentions at all.
+    pub fn none() -> Self {
+        AllowedMentions {
+            everyone: false,
+            users: MentionPolicy::Only(Vec::new()),
+            roles: MentionPolicy::Only(Vec::new()),
+            replied_user: false,
+        }
+    }
 
-            data: Some(discord_types::InteractionCallbackData {
-                content: Some(self.text),
-                components: Some(rows),
-                flags: Some(if self.ephemeral { 64 } else { 0 }),
-                custom_id: None,
-                title: None,
-            }),
+    /// Allows every mention, including roles, `@everyone`, and `@here`.
+    pub fn all() -> Self {
+        AllowedMentions {
+            everyone: true,
+            users: MentionPolicy::All,
+            roles: MentionPolicy::All,
+            replied_user: true,
         }
     }
-}
 
-impl Into<discord_types::InteractionResponse> for Modal {
-    fn into(self) -> discord_types::InteractionResponse {
-        let fields = self
-            .fields
-            .iter()
-            .map(|field| discord_types::Component {
-                r#type: discord_types::ComponentType::ActionRow,
-                label: None,
-                style: None,
-                custom_id: None,
-                value: None,
-                components: Some(vec![discord_types::Component {
-                    r#type: discord_types::ComponentType::TextInput,
-                    label: Some(field.label.clone()),
-                    style: Some(1),
-                    custom_id: Some(field.id.clone()),
-                    value: None,
-                    components: None,
-                }]),
-            })
-            .collect();
+    /// Allows `@everyone` and `@here` to notify anyone.
+    pub fn everyone(mut self) -> Self {
+        self.everyone = true;
+        self
+    }
 
-        let data = discord_types::InteractionCallbackData {
-            content: None,
-            flags: None,
-            components: Some(fields),
-            custom_id: Some(self.id),
-            title: Some(self.title),
-        };
+    /// Allows any user to be mentioned.
+    pub fn all_users(mut self) -> Self {
+        self.users = MentionPolicy::All;
+        self
+    }
 
-        discord_types::InteractionResponse {
-            r#type: discord_types::InteractionCallbackType::Modal,
-            data: Some(data),
-        }
+    /// Allows the user with the given id to be mentioned. Once a user is listed, only listed users
+    /// can be mentioned.
+    pub fn user(mut self, id: Snowflake) -> Self {
+        self.users.allow(id);
+        self
+    }
+
+    /// Allows any role to be mentioned.
+    pub fn all_roles(mut self) -> Self {
+        self.roles = MentionPolicy::All;
+        self
+    }
+
+    /// Allows the role with the given id to be mentioned. Once a role is listed, only listed roles
+    /// can be mentioned.
+    pub fn role(mut self, id: Snowflake) -> Self {
+        self.roles.allow(id);
+        self
+    }
+
+    /// Notifies the author of the message being replied to.
+    pub fn replied_user(mut self) -> Self {
+        self.replied_user = true;
+        self
     }
 }
 
-impl Into<discord_types::Component> for &Button {
-    fn into(self) -> discord_types::Component {
-        discord_types::Component {
-            r#type: discord_types::ComponentType::Button,
-            label: Some(self.text.clone()),
-            style: Some(1),
-            custom_id: Some(self.id.clone()),
-            value: None,
-            components: None,
+impl MentionPolicy {
+    fn allow(&mut self, id: Snowflake) {
+        match self {
+            MentionPolicy::Only(ids) => ids.push(id),
+            MentionPolicy::All => *self = MentionPolicy::Only(vec![id]),
         }
     }
 }
+
+impl BitOr for MessageFlags {
+    type Output = MessageFlags;
+
+    fn bitor(self, other: MessageFlags) -> MessageFlags {
+        MessageFlags(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for MessageFlags {
+    fn bitor_assign(&mut self, other: MessageFlags) {
+        self.0 |= other.0;
+    }
+}
+
+impl Default for ActionRow {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Convenience methods for building action rows.
+impl ActionRow {
+    /// Creates a new, empty row.
+    pub fn new() -> Self {
+        ActionRow {
+            components: Vec::new(),
+        }
+    }
+
+    /// Adds a button to the row.
+    pub fn button(self, id: &str, text: &str) -> Self {
+        self.button_with(Button::new(id, text))
+    }
+
+    /// Adds a link button, which opens the given URL when clicked, to the row.
+    pub fn link_button(self, url: &str, text: &str) -> Self {
+        self.button_with(Button::link(url, text))
+    }
+
+    /// Adds a fully customized button to the row.
+    pub fn button_with(mut self, button: Button) -> Self {
+        self.components.push(RowComponent::Button(button));
+        self
+    }
+
+    /// Adds a select menu to the row. A select menu must be the only component in its row.
+    pub fn select(mut self, select: SelectMenu) -> Self {
+        self.components.push(RowComponent::Select(select));
+        self
+    }
+
+    /// Returns true if the row only holds buttons, and fewer than 5 of them.
+    fn has_room_for_button(&self) -> bool {
+        self.components.len() < 5
+            && self
+                .components
+                .iter()
+                .all(|c| matches!(c, RowComponent::Button(_)))
+    }
+
+    pub(crate) fn for_each_button(&mut self, f: &mut dyn FnMut(&mut Button)) {
+        for component in &mut self.components {
+            if let RowComponent::Button(button) = component {
+                f(button);
+            }
+        }
+    }
+
+    pub(crate) fn for_each_select(&mut self, f: &mut dyn FnMut(&mut SelectMenu)) {
+        for component in &mut self.components {
+            if let RowComponent::Select(select) = component {
+                f(select);
+            }
+        }
+    }
+}
+
+/// Convenience methods for building buttons.
+impl Button {
+    /// Creates a new primary button with the given id and text.
+    pub fn new(id: &str, text: &str) -> Self {
+        Button {
+            id: Some(id.to_string()),
+            text: text.to_string(),
+            style: ButtonStyle::Primary,
+            emoji: None,
+            url: None,
+            sku_id: None,
+            disabled: false,
+        }
+    }
+
+    /// Creates a new link button, which opens the given URL when clicked.
+    pub fn link(url: &str, text: &str) -> Self {
+        Button {
+            id: None,
+            url: Some(url.to_string()),
+            style: ButtonStyle::Link,
+            ..Button::new("", text)
+        }
+    }
+
+    /// Creates a new premium button, which prompts the user to purchase the given SKU. Discord
+    /// supplies the button's text and emoji from the SKU.
+    pub fn premium(sku_id: Snowflake) -> Self {
+        Button {
+            id: None,
+            sku_id: Some(sku_id),
+            style: ButtonStyle::Premium,
+            ..Button::new("", "")
+        }
+    }
+
+    /// Sets the style of the button.
+    pub fn style(mut self, style: ButtonStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets the emoji shown on the button.
+    pub fn emoji(mut self, emoji: Emoji) -> Self {
+        self.emoji = Some(emoji);
+        self
+    }
+
+    /// Sets the button to be disabled.
+    pub fn disabled(mut self) -> Self {
+        self.disabled = true;
+        self
+    }
+}
+
+/// Convenience methods for building select menus.
+impl SelectMenu {
+    /// Creates a new select menu with the given id, which lets the user pick a single option.
+    pub fn new(id: &str) -> Self {
+        SelectMenu {
+            id: id.to_string(),
+            kind: SelectKind::String,
+            options: Vec::new(),
+            channel_types: Vec::new(),
+            default_values: Vec::new(),
+            placeholder: None,
+            min_values: 1,
+            max_values: 1,
+            disabled: false,
+        }
+    }
+
+    /// Creates a new select menu with the given id, populated with the guild's users.
+    pub fn users(id: &str) -> Self {
+        SelectMenu {
+            kind: SelectKind::User,
+            ..SelectMenu::new(id)
+        }
+    }
+
+    /// Creates a new select menu with the given id, populated with the guild's roles.
+    pub fn roles(id: &str) -> Self {
+        SelectMenu {
+            kind: SelectKind::Role,
+            ..SelectMenu::new(id)
+        }
+    }
+
+    /// Creates a new select menu with the given id, populated with the guild's users and roles.
+    pub fn mentionables(id: &str) -> Self {
+        SelectMenu {
+            kind: SelectKind::Mentionable,
+            ..SelectMenu::new(id)
+        }
+    }
+
+    /// Creates a new select menu with the given id, populated with the guild's channels.
+    pub fn channels(id: &str) -> Self {
+        SelectMenu {
+            kind: SelectKind::Channel,
+            ..SelectMenu::new(id)
+        }
+    }
+
+    /// Limits a channel select menu to channels of the given type. May be called several times to
+    /// allow several types.
+    pub fn channel_type(mut self, channel_type: ChannelType) -> Self {
+        self.channel_types.push(channel_type);
+        self
+    }
+
+    /// Adds an item that an auto-populated select menu shows as picked when it is first shown.
+    pub fn default_value(mut self, default: SelectDefault) -> Self {
+        self.default_values.push(default);
+        self
+    }
+
+    /// Adds an option to the select menu.
+    pub fn option(mut self, option: SelectOption) -> Self {
+        self.options.push(option);
+        self
+    }
+
+    /// Sets the text shown when nothing is picked.
+    pub fn placeholder(mut self, placeholder: &str) -> Self {
+        self.placeholder = Some(placeholder.to_string());
+        self
+    }
+
+    /// Sets the minimum number of options that must be picked.
+    pub fn min_values(mut self, min_values: u8) -> Self {
+        self.min_values = min_values;
+        self
+    }
+
+    /// Sets the maximum number of options that can be picked.
+    pub fn max_values(mut self, max_values: u8) -> Self {
+        self.max_values = max_values;
+        self
+    }
+
+    /// Sets the select menu to be disabled.
+    pub fn disabled(mut self) -> Self {
+        self.disabled = true;
+        self
+    }
+}
+
+/// Convenience methods for building select menu options.
+impl SelectOption {
+    /// Creates a new option with the given label and value.
+    pub fn new(label: &str, value: &str) -> Self {
+        SelectOption {
+            label: label.to_string(),
+            value: value.to_string(),
+            description: None,
+            emoji: None,
+            selected: false,
+        }
+    }
+
+    /// Sets the description shown below the option's label.
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Sets the emoji shown next to the option.
+    pub fn emoji(mut self, emoji: Emoji) -> Self {
+        self.emoji = Some(emoji);
+        self
+    }
+
+    /// Sets the option to be picked when the menu is first shown.
+    pub fn selected(mut self) -> Self {
+        self.selected = true;
+        self
+    }
+}
+
+/// Convenience methods for building emoji.
+impl Emoji {
+    /// Creates a unicode emoji, such as `"🔥"`.
+    pub fn unicode(emoji: &str) -> Self {
+        Emoji {
+            id: None,
+            name: Some(emoji.to_string()),
+            animated: false,
+        }
+    }
+
+    /// Creates a custom emoji with the given id and name.
+    pub fn custom(id: Snowflake, name: &str) -> Self {
+        Emoji {
+            id: Some(id),
+            name: Some(name.to_string()),
+            animated: false,
+        }
+    }
+
+    /// Sets the custom emoji to be animated.
+    pub fn animated(mut self) -> Self {
+        self.animated = true;
+        self
+    }
+}
+
+impl Default for Modal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Convenience methods for building modals.
+impl Modal {
+    /// Creates a new modal.
+    pub fn new() -> Self {
+        Modal {
+            id: "unknown modal".to_string(),
+            title: "".to_string(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Sets the `id` of the modal.
+    pub fn id(mut self, id: &str) -> Self {
+        self.id = id.to_string();
+        self
+    }
+
+    /// Sets the `title` of the modal.
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+
+    /// Adds a field to the modal.
+    pub fn field(mut self, id: &str, label: &str) -> Self {
+        self.fields.push(TextField::new(id, label));
+        self
+    }
+
+    /// Adds a multi-line field to the modal.
+    pub fn paragraph(mut self, id: &str, label: &str) -> Self {
+        self.fields.push(TextField::new(id, label).paragraph());
+        self
+    }
+
+    /// Adds a fully customized field to the modal.
+    pub fn field_with(mut self, field: TextField) -> Self {
+        self.fields.push(field);
+        self
+    }
+}
+
+/// Convenience methods for building text fields.
+impl TextField {
+    /// Creates a new, required, single-line text field.
+    pub fn new(id: &str, label: &str) -> Self {
+        TextField {
+            id: id.to_string(),
+            label: label.to_string(),
+            style: TextInputStyle::Short,
+            min_length: None,
+            max_length: None,
+            required: true,
+            placeholder: None,
+            value: None,
+        }
+    }
+
+    /// Sets the field to allow multiple lines of text.
+    pub fn paragraph(mut self) -> Self {
+        self.style = TextInputStyle::Paragraph;
+        self
+    }
+
+    /// Sets the minimum number of characters the user must enter.
+    pub fn min_length(mut self, min_length: u16) -> Self {
+        self.min_length = Some(min_length);
+        self
+    }
+
+    /// Sets the maximum number of characters the user may enter.
+    pub fn max_length(mut self, max_length: u16) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    /// Sets the field to be optional.
+    pub fn optional(mut self) -> Self {
+        self.required = false;
+        self
+    }
+
+    /// Sets the text shown when the field is empty.
+    pub fn placeholder(mut self, placeholder: &str) -> Self {
+        self.placeholder = Some(placeholder.to_string());
+        self
+    }
+
+    /// Prefills the field with the given text.
+    pub fn value(mut self, value: &str) -> Self {
+        self.value = Some(value.to_string());
+        self
+    }
+}
+
+impl MessageComponent {
+    /// Checks whether the user who used the component is the user that its message responds to.
+    /// This is also true if the message isn't a response to an interaction.
+    pub fn is_from_invoker(&self) -> bool {
+        self.source
+            .interaction_metadata
+            .as_ref()
+            .is_none_or(|m| m.user.id == self.user_id)
+    }
+}
+
+impl ModalSubmit {
+    /// Returns the submitted text of the given field, or `None` if the field was left empty or
+    /// does not exist.
+    pub fn value(&self, id: &str) -> Option<&str> {
+        self.values
+            .get(id)
+            .map(|v| v.as_str())
+            .filter(|v| !v.is_empty())
+    }
+}
+
+impl From<&discord_types::InteractionRequest> for InteractionToken {
+    fn from(req: &discord_types::InteractionRequest) -> Self {
+        InteractionToken {
+            interaction_id: req.id,
+            application_id: req.application_id,
+            token: req.token.clone(),
+        }
+    }
+}
+
+impl TryFrom<&discord_types::InteractionRequest> for ApplicationCommand {
+    type Error = ConversionError;
+
+    fn try_from(req: &discord_types::InteractionRequest) -> Result<Self, Self::Error> {
+        let data = interaction_data(req)?;
+
+        Ok(ApplicationCommand {
+            command_name: data
+                .name
+                .clone()
+                .ok_or(ConversionError::MissingField("data.name"))?,
+            user_id: invoking_user(req)?.id,
+            token: req.into(),
+            raw: req.raw.clone(),
+        })
+    }
+}
+
+impl TryFrom<&discord_types::InteractionRequest> for Autocomplete {
+    type Error = ConversionError;
+
+    fn try_from(req: &discord_types::InteractionRequest) -> Result<Self, Self::Error> {
+        let data = interaction_data(req)?;
+        let focused = find_focused_option(data.options.as_deref().unwrap_or_default())
+            .ok_or(ConversionError::MissingField("data.options.focused"))?;
+
+        Ok(Autocomplete {
+            command_name: data
+                .name
+                .clone()
+                .ok_or(ConversionError::MissingField("data.name"))?,
+            user_id: invoking_user(req)?.id,
+            option: focused.name.clone(),
+            input: match &focused.value {
+                Some(serde_json::Value::String(s)) => s.clone(),
+                Some(v) => v.to_string(),
+                None => "".to_string(),
+            },
+            locale: req.locale.clone(),
+            raw: req.raw.clone(),
+        })
+    }
+}
+
+/// Finds the option that the user is currently typing into, searching through subcommands and
+/// subcommand groups.
+fn find_focused_option(
+    options: &[discord_types::InteractionDataOption],
+) -> Option<&discord_types::InteractionDataOption> {
+    options.iter().find_map(|option| {
+        if option.focused == Some(true) {
+            Some(option)
+        } else {
+            find_focused_option(option.options.as_deref().unwrap_or_default())
+        }
+    })
+}
+
+/// Looks up the data of an interaction, which every interaction but a ping has.
+fn interaction_data(
+    req: &discord_types::InteractionRequest,
+) -> Result<&discord_types::InteractionData, ConversionError> {
+    req.data
+        .as_ref()
+        .ok_or(ConversionError::MissingField("data"))
+}
+
+/// Looks up the user who triggered an interaction, who is given as a guild member in guilds, and
+/// as a plain user in DMs.
+fn invoking_user(
+    req: &discord_types::InteractionRequest,
+) -> Result<&discord_types::User, ConversionError> {
+    req.member
+        .as_ref()
+        .map(|m| &m.user)
+        .or(req.user.as_ref())
+        .ok_or(ConversionError::MissingField("member.user"))
+}
+
+impl TryFrom<&discord_types::InteractionRequest> for MessageComponent {
+    type Error = ConversionError;
+
+    fn try_from(req: &discord_types::InteractionRequest) -> Result<Self, Self::Error> {
+        let data = interaction_data(req)?;
+        let values = data.values.clone().unwrap_or_default();
+        let resolved = data
+            .resolved
+            .as_ref()
+            .map(|r| Resolved::from_values(r, &values))
+            .unwrap_or_default();
+
+        let id = data
+            .custom_id
+            .as_ref()
+            .ok_or(ConversionError::MissingField("data.custom_id"))?;
+        let message = req
+            .message
+            .as_ref()
+            .ok_or(ConversionError::MissingField("message"))?;
+
+        Ok(MessageComponent {
+            id: strip_invoker_only(id).to_string(),
+
+            user_id: invoking_user(req)?.id,
+
+            values,
+
+            resolved,
+
+            token: req.into(),
+
+            source: message.into(),
+
+            raw: req.raw.clone(),
+        })
+    }
+}
+
+impl TryFrom<&discord_types::InteractionRequest> for ModalSubmit {
+    type Error = ConversionError;
+
+    fn try_from(req: &discord_types::InteractionRequest) -> Result<Self, Self::Error> {
+        let data = interaction_data(req)?;
+
+        let values = data
+            .components
+            .as_ref()
+            .ok_or(ConversionError::MissingField("data.components"))?
+            .iter()
+            .flat_map(|row| row.components.iter().flatten())
+            .map(|input| {
+                let id = input
+                    .custom_id
+                    .clone()
+                    .ok_or(ConversionError::MissingField("data.components.custom_id"))?;
+                Ok((id, input.value.clone().unwrap_or_default()))
+            })
+            .collect::<Result<_, ConversionError>>()?;
+
+        Ok(ModalSubmit {
+            id: data
+                .custom_id
+                .clone()
+                .ok_or(ConversionError::MissingField("data.custom_id"))?,
+
+            values,
+
+            token: req.into(),
+
+            origin: if req.message.is_some() {
+                ModalOrigin::MessageComponent
+            } else {
+                ModalOrigin::ApplicationCommand
+            },
+
+            source: req.message.as_ref().map(|m| m.into()),
+
+            raw: req.raw.clone(),
+        })
+    }
+}
+
+impl From<&discord_types::Message> for SourceMessage {
+    fn from(msg: &discord_types::Message) -> Self {
+        let flags = MessageFlags::from_bits(msg.flags.unwrap_or(0));
+        let (rows, layout) = if flags.contains(MessageFlags::IS_COMPONENTS_V2) {
+            let layout = msg.components.iter().map(parse_layout).collect();
+            (Vec::new(), layout)
+        } else {
+            let rows = msg.components.iter().filter_map(parse_row).collect();
+            (rows, Vec::new())
+        };
+
+        SourceMessage {
+            id: msg.id,
+            channel_id: msg.channel_id,
+            author: (&msg.author).into(),
+            timestamp: msg.timestamp.clone(),
+            edited_timestamp: msg.edited_timestamp.clone(),
+            text: msg.content.clone(),
+            embeds: msg.embeds.iter().map(|e| e.into()).collect(),
+            rows,
+            layout,
+            flags,
+            poll: msg.poll.as_ref().map(|p| p.into()),
+            invoker_only: msg.components.iter().any(has_invoker_only_id),
+            interaction_metadata: msg
+                .interaction_metadata
+                .as_ref()
+                .map(|m| InteractionMetadata {
+                    id: m.id,
+                    kind: m.r#type.into(),
+                    user: (&m.user).into(),
+                    original_response_message_id: m.original_response_message_id,
+                    interacted_message_id: m.interacted_message_id,
+                }),
+        }
+    }
+}
+
+impl From<&discord_types::User> for User {
+    fn from(user: &discord_types::User) -> Self {
+        User {
+            id: user.id,
+            username: user.username.clone().unwrap_or_default(),
+            global_name: user.global_name.clone(),
+            nick: None,
+            bot: user.bot.unwrap_or(false),
+        }
+    }
+}
+
+impl From<u8> for InteractionKind {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => InteractionKind::Ping,
+            2 => InteractionKind::ApplicationCommand,
+            3 => InteractionKind::MessageComponent,
+            4 => InteractionKind::Autocomplete,
+            5 => InteractionKind::ModalSubmit,
+            other => InteractionKind::Unknown(other),
+        }
+    }
+}
+
+/// Removes the invoker-only mark from a component's custom id, if it has one.
+fn strip_invoker_only(id: &str) -> &str {
+    id.strip_prefix(INVOKER_ONLY_PREFIX).unwrap_or(id)
+}
+
+/// Checks whether a component, or any component nested within it, is marked as invoker-only.
+fn has_invoker_only_id(component: &discord_types::Component) -> bool {
+    component
+        .custom_id
+        .as_ref()
+        .is_some_and(|id| id.starts_with(INVOKER_ONLY_PREFIX))
+        || component
+            .components
+            .iter()
+            .flatten()
+            .any(has_invoker_only_id)
+        || component
+            .accessory
+            .as_deref()
+            .is_some_and(has_invoker_only_id)
+}
+
+/// Parses an action row of a received message, keeping any components that this library doesn't
+/// know as raw JSON. Returns `None` if the row holds no components.
+pub(crate) fn parse_row(component: &discord_types::Component) -> Option<ActionRow> {
+    if component.r#type != discord_types::ComponentType::ActionRow {
+        return None;
+    }
+
+    let components: Vec<RowComponent> = component
+        .components
+        .iter()
+        .flatten()
+        .map(|c| {
+            let known = match c.r#type {
+                discord_types::ComponentType::Button => parse_button(c).map(RowComponent::Button),
+                _ => parse_select(c).map(RowComponent::Select),
+            };
+            known.unwrap_or_else(|| RowComponent::Unknown(unknown_from_wire(c)))
+        })
+        .collect();
+
+    (!components.is_empty()).then_some(ActionRow { components })
+}
+
+/// Keeps a component that this library doesn't know as raw JSON, so that it can be sent back.
+pub(crate) fn unknown_from_wire(component: &discord_types::Component) -> serde_json::Value {
+    serde_json::to_value(component).unwrap_or_default()
+}
+
+/// Converts a component kept as raw JSON back for sending.
+pub(crate) fn unknown_into_wire(
+    component: &serde_json::Value,
+) -> Result<discord_types::Component, ValidationError> {
+    serde_json::from_value(component.clone()).map_err(|_| {
+        ValidationError::Invalid("an unknown component must be a JSON object with a numeric type")
+    })
+}
+
+/// Parses a button of a received message.
+pub(crate) fn parse_button(component: &discord_types::Component) -> Option<Button> {
+    if component.r#type != discord_types::ComponentType::Button {
+        return None;
+    }
+
+    let style = match component.style? {
+        1 => ButtonStyle::Primary,
+        2 => ButtonStyle::Secondary,
+        3 => ButtonStyle::Success,
+        4 => ButtonStyle::Danger,
+        5 => ButtonStyle::Link,
+        6 => ButtonStyle::Premium,
+        _ => return None,
+    };
+
+    Some(Button {
+        id: component
+            .custom_id
+            .as_deref()
+            .map(|id| strip_invoker_only(id).to_string()),
+        text: component.label.clone().unwrap_or_default(),
+        style,
+        emoji: component.emoji.as_ref().map(|e| e.into()),
+        url: component.url.clone(),
+        sku_id: component.sku_id,
+        disabled: component.disabled.unwrap_or(false),
+    })
+}
+
+/// Parses a select menu of a received message.
+fn parse_select(component: &discord_types::Component) -> Option<SelectMenu> {
+    let kind = match component.r#type {
+        discord_types::ComponentType::StringSelect => SelectKind::String,
+        discord_types::ComponentType::UserSelect => SelectKind::User,
+        discord_types::ComponentType::RoleSelect => SelectKind::Role,
+        discord_types::ComponentType::MentionableSelect => SelectKind::Mentionable,
+        discord_types::ComponentType::ChannelSelect => SelectKind::Channel,
+        _ => return None,
+    };
+
+    Some(SelectMenu {
+        id: strip_invoker_only(component.custom_id.as_ref()?).to_string(),
+        kind,
+        options: component
+            .options
+            .iter()
+            .flatten()
+            .map(|option| SelectOption {
+                label: option.label.clone(),
+                value: option.value.clone(),
+                description: option.description.clone(),
+                emoji: option.emoji.as_ref().map(|e| e.into()),
+                selected: option.default.unwrap_or(false),
+            })
+            .collect(),
+        channel_types: component
+            .channel_types
+            .iter()
+            .flatten()
+            .map(|&t| t.into())
+            .collect(),
+        default_values: component
+            .default_values
+            .iter()
+            .flatten()
+            .filter_map(|default| match default.r#type.as_str() {
+                "user" => Some(SelectDefault::User(default.id)),
+                "role" => Some(SelectDefault::Role(default.id)),
+                "channel" => Some(SelectDefault::Channel(default.id)),
+                _ => None,
+            })
+            .collect(),
+        placeholder: component.placeholder.clone(),
+        min_values: component.min_values.unwrap_or(1),
+        max_values: component.max_values.unwrap_or(1),
+        disabled: component.disabled.unwrap_or(false),
+    })
+}
+
+impl TryFrom<Response> for discord_types::InteractionResponse {
+    type Error = ValidationError;
+
+    fn try_from(res: Response) -> Result<Self, Self::Error> {
+        Ok(match res {
+            Response::Message(m) => m.try_into()?,
+            Response::Modal(m) => m.try_into()?,
+
+            Response::Defer { ephemeral } => discord_types::InteractionResponse {
+                r#type: discord_types::InteractionCallbackType::DeferredChannelMessageWithSource,
+                data: Some(discord_types::InteractionCallbackData {
+                    flags: Some(if ephemeral {
+                        MessageFlags::EPHEMERAL.bits()
+                    } else {
+                        0
+                    }),
+                    ..Default::default()
+                }),
+                followups: Vec::new(),
+                raw: None,
+            },
+
+            Response::DeferUpdate => discord_types::InteractionResponse {
+                r#type: discord_types::InteractionCallbackType::DeferredUpdateMessage,
+                data: None,
+                followups: Vec::new(),
+                raw: None,
+            },
+
+            Response::Acknowledge { followups } => discord_types::InteractionResponse {
+                r#type: discord_types::InteractionCallbackType::DeferredUpdateMessage,
+                data: None,
+                followups: followups
+                    .into_iter()
+                    .map(|m| m.try_into())
+                    .collect::<Result<_, ValidationError>>()?,
+                raw: None,
+            },
+
+            Response::Raw(raw) => discord_types::InteractionResponse::raw(raw),
+        })
+    }
+}
+
+impl TryFrom<Message> for discord_types::InteractionResponse {
+    type Error = ValidationError;
+
+    fn try_from(msg: Message) -> Result<Self, Self::Error> {
+        if msg.edit && msg.poll.is_some() {
+            return Err(ValidationError::Unsupported(
+                "a poll cannot be attached by editing a message",
+            ));
+        }
+
+        Ok(discord_types::InteractionResponse {
+            r#type: if msg.edit {
+                discord_types::InteractionCallbackType::UpdateMessage
+            } else {
+                discord_types::InteractionCallbackType::ChannelMessageWithSource
+            },
+
+            data: Some(msg.try_into()?),
+            followups: Vec::new(),
+            raw: None,
+        })
+    }
+}
+
+impl TryFrom<Message> for discord_types::InteractionCallbackData {
+    type Error = ValidationError;
+
+    fn try_from(mut msg: Message) -> Result<Self, Self::Error> {
+        if msg.invoker_only {
+            msg.for_each_button(&mut |button| {
+                if let Some(id) = &mut button.id {
+                    id.insert_str(0, INVOKER_ONLY_PREFIX);
+                }
+            });
+            msg.for_each_select(&mut |select| select.id.insert_str(0, INVOKER_ONLY_PREFIX));
+        }
+
+        let (attachments, files) = attachments_into_wire(msg.attachments)?;
+
+        if !msg.layout.is_empty() || msg.flags.contains(MessageFlags::IS_COMPONENTS_V2) {
+            if !msg.text.is_empty() || !msg.embeds.is_empty() || msg.poll.is_some() {
+                return Err(ValidationError::Invalid(
+                    "a message with a layout cannot also have text, embeds, or a poll",
+                ));
+            }
+
+            return Ok(discord_types::InteractionCallbackData {
+                components: Some(layout_into_wire(&msg.layout, &msg.rows)?),
+                flags: Some((msg.flags | MessageFlags::IS_COMPONENTS_V2).bits()),
+                allowed_mentions: Some(msg.allowed_mentions.try_into()?),
+                attachments,
+                files,
+                ..Default::default()
+            });
+        }
+
+        check_count("action rows", msg.rows.len(), 5)?;
+
+        let rows = msg
+            .rows
+            .iter()
+            .map(|row| row.try_into())
+            .collect::<Result<_, ValidationError>>()?;
+
+        Ok(discord_types::InteractionCallbackData {
+            content: Some(msg.text),
+            components: Some(rows),
+            embeds: Some(embeds_into_wire(msg.embeds)?),
+            flags: Some(msg.flags.bits()),
+            allowed_mentions: Some(msg.allowed_mentions.try_into()?),
+            attachments,
+            poll: msg.poll.map(|p| p.try_into()).transpose()?,
+            files,
+            ..Default::default()
+        })
+    }
+}
+
+/// Checks a message's attachments against Discord's limits, splitting them into the list sent in
+/// the message's JSON and the files uploaded alongside it.
+fn attachments_into_wire(
+    attachments: Vec<Attachment>,
+) -> Result<
+    (
+        Option<Vec<discord_types::PartialAttachment>>,
+        Vec<discord_types::FileUpload>,
+    ),
+    ValidationError,
+> {
+    if attachments.is_empty() {
+        return Ok((None, Vec::new()));
+    }
+
+    check_count("attachments", attachments.len(), 10)?;
+
+    let mut listed = Vec::new();
+    let mut files = Vec::new();
+    for (id, attachment) in attachments.into_iter().enumerate() {
+        check_length("attachment filename", &attachment.filename, 1, 1024)?;
+        if let Some(description) = &attachment.description {
+            check_length("attachment description", description, 1, 1024)?;
+        }
+
+        listed.push(discord_types::PartialAttachment {
+            id,
+            filename: attachment.filename.clone(),
+            description: attachment.description,
+        });
+        files.push(discord_types::FileUpload {
+            filename: attachment.filename,
+            data: attachment.data,
+        });
+    }
+
+    Ok((Some(listed), files))
+}
+
+impl TryFrom<AllowedMentions> for discord_types::AllowedMentions {
+    type Error = ValidationError;
+
+    fn try_from(mentions: AllowedMentions) -> Result<Self, Self::Error> {
+        let mut parse = Vec::new();
+        if mentions.everyone {
+            parse.push("everyone".to_string());
+        }
+
+        let users = match mentions.users {
+            MentionPolicy::All => {
+                parse.push("users".to_string());
+                Vec::new()
+            }
+            MentionPolicy::Only(ids) => ids,
+        };
+        let roles = match mentions.roles {
+            MentionPolicy::All => {
+                parse.push("roles".to_string());
+                Vec::new()
+            }
+            MentionPolicy::Only(ids) => ids,
+        };
+
+        check_count("allowed user mentions", users.len(), 100)?;
+        check_count("allowed role mentions", roles.len(), 100)?;
+
+        Ok(discord_types::AllowedMentions {
+            parse,
+            users,
+            roles,
+            replied_user: mentions.replied_user,
+        })
+    }
+}
+
+impl TryFrom<&ActionRow> for discord_types::Component {
+    type Error = ValidationError;
+
+    fn try_from(row: &ActionRow) -> Result<Self, Self::Error> {
+        let selects = row
+            .components
+            .iter()
+            .filter(|c| matches!(c, RowComponent::Select(_)))
+            .count();
+
+        if row.components.is_empty() {
+            return Err(ValidationError::Invalid(
+                "an action row must hold at least one component",
+            ));
+        }
+        if selects > 0 && row.components.len() > 1 {
+            return Err(ValidationError::Invalid(
+                "a select menu must be the only component in its action row",
+            ));
+        }
+        check_count("buttons in an action row", row.components.len(), 5)?;
+
+        let components = row
+            .components
+            .iter()
+            .map(|component| match component {
+                RowComponent::Button(button) => button.try_into(),
+                RowComponent::Select(select) => select.try_into(),
+                RowComponent::Unknown(unknown) => unknown_into_wire(unknown),
+            })
+            .collect::<Result<_, ValidationError>>()?;
+
+        Ok(discord_types::Component {
+            components: Some(components),
+            ..discord_types::Component::new(discord_types::ComponentType::ActionRow)
+        })
+    }
+}
+
+impl TryFrom<Modal> for discord_types::InteractionResponse {
+    type Error = ValidationError;
+
+    fn try_from(modal: Modal) -> Result<Self, Self::Error> {
+        check_length("modal custom id", &modal.id, 1, 100)?;
+        check_length("modal title", &modal.title, 1, 45)?;
+        check_count("modal fields", modal.fields.len(), 5)?;
+
+        let fields = modal
+            .fields
+            .iter()
+            .map(|field| {
+                Ok(discord_types::Component {
+                    components: Some(vec![field.try_into()?]),
+                    ..discord_types::Component::new(discord_types::ComponentType::ActionRow)
+                })
+            })
+            .collect::<Result<_, ValidationError>>()?;
+
+        let data = discord_types::InteractionCallbackData {
+            components: Some(fields),
+            custom_id: Some(modal.id),
+            title: Some(modal.title),
+            ..Default::default()
+        };
+
+        Ok(discord_types::InteractionResponse {
+            r#type: discord_types::InteractionCallbackType::Modal,
+            data: Some(data),
+            followups: Vec::new(),
+            raw: None,
+        })
+    }
+}
+
+impl TryFrom<&TextField> for discord_types::Component {
+    type Error = ValidationError;
+
+    fn try_from(field: &TextField) -> Result<Self, Self::Error> {
+        check_length("text field custom id", &field.id, 1, 100)?;
+        check_length("text field label", &field.label, 1, 45)?;
+        if let Some(placeholder) = &field.placeholder {
+            check_length("text field placeholder", placeholder, 0, 100)?;
+        }
+
+        let min = field.min_length.unwrap_or(0);
+        let max = field.max_length.unwrap_or(4000);
+        if min > 4000 || !(1..=4000).contains(&max) {
+            return Err(ValidationError::Invalid(
+                "a text field's length limits must be between 0 and 4000, and its max at least 1",
+            ));
+        }
+        if min > max {
+            return Err(ValidationError::Invalid(
+                "a text field's min length cannot exceed its max length",
+            ));
+        }
+        if let Some(value) = &field.value {
+            check_length("text field value", value, 0, max as usize)?;
+        }
+
+        Ok(discord_types::Component {
+            label: Some(field.label.clone()),
+            style: Some(discord_types::TextInputStyle::from(field.style).into()),
+            custom_id: Some(field.id.clone()),
+            value: field.value.clone(),
+            placeholder: field.placeholder.clone(),
+            min_length: field.min_length,
+            max_length: field.max_length,
+            required: Some(field.required),
+            ..discord_types::Component::new(discord_types::ComponentType::TextInput)
+        })
+    }
+}
+
+impl From<TextInputStyle> for discord_types::TextInputStyle {
+    fn from(style: TextInputStyle) -> Self {
+        match style {
+            TextInputStyle::Short => discord_types::TextInputStyle::Short,
+            TextInputStyle::Paragraph => discord_types::TextInputStyle::Paragraph,
+        }
+    }
+}
+
+impl TryFrom<&Button> for discord_types::Component {
+    type Error = ValidationError;
+
+    fn try_from(button: &Button) -> Result<Self, Self::Error> {
+        match button.style {
+            ButtonStyle::Link => {
+                if button.id.is_some() {
+                    return Err(ValidationError::Invalid(
+                        "a link button cannot have a custom id",
+                    ));
+                }
+                if button.url.is_none() {
+                    return Err(ValidationError::Invalid("a link button must have a url"));
+                }
+                if button.sku_id.is_some() {
+                    return Err(ValidationError::Invalid(
+                        "only a premium button can have a sku id",
+                    ));
+                }
+            }
+
+            ButtonStyle::Premium => {
+                if button.id.is_some() || button.url.is_some() {
+                    return Err(ValidationError::Invalid(
+                        "a premium button cannot have a custom id or url",
+                    ));
+                }
+                if !button.text.is_empty() || button.emoji.is_some() {
+                    return Err(ValidationError::Invalid(
+                        "a premium button cannot have text or an emoji",
+                    ));
+                }
+                if button.sku_id.is_none() {
+                    return Err(ValidationError::Invalid(
+                        "a premium button must have a sku id",
+                    ));
+                }
+            }
+
+            _ => {
+                if button.url.is_some() {
+                    return Err(ValidationError::Invalid(
+                        "only a link button can have a url",
+                    ));
+                }
+                if button.sku_id.is_some() {
+                    return Err(ValidationError::Invalid(
+                        "only a premium button can have a sku id",
+                    ));
+                }
+            }
+        }
+
+        if let Some(id) = &button.id {
+            check_length("button custom id", id, 1, 100)?;
+        } else if !matches!(button.style, ButtonStyle::Link | ButtonStyle::Premium) {
+            return Err(ValidationError::Invalid("a button must have a custom id"));
+        }
+
+        check_length("button text", &button.text, 0, 80)?;
+
+        Ok(discord_types::Component {
+            label: (!button.text.is_empty()).then(|| button.text.clone()),
+            style: Some(discord_types::ButtonStyle::from(button.style).into()),
+            custom_id: button.id.clone(),
+            url: button.url.clone(),
+            emoji: button.emoji.clone().map(|e| e.into()),
+            disabled: button.disabled.then_some(true),
+            sku_id: button.sku_id,
+            ..discord_types::Component::new(discord_types::ComponentType::Button)
+        })
+    }
+}
+
+impl TryFrom<&SelectMenu> for discord_types::Component {
+    type Error = ValidationError;
+
+    fn try_from(select: &SelectMenu) -> Result<Self, Self::Error> {
+        check_length("select menu custom id", &select.id, 1, 100)?;
+        if let Some(placeholder) = &select.placeholder {
+            check_length("select menu placeholder", placeholder, 0, 150)?;
+        }
+        if !(1..=25).contains(&select.max_values) {
+            return Err(ValidationError::Invalid(
+                "a select menu must allow between 1 and 25 picks",
+            ));
+        }
+        if select.min_values > select.max_values {
+            return Err(ValidationError::Invalid(
+                "a select menu's min values cannot exceed its max values",
+            ));
+        }
+
+        if select.kind == SelectKind::String {
+            check_count("select menu options", select.options.len(), 25)?;
+            if select.options.is_empty() {
+                return Err(ValidationError::Invalid(
+                    "a string select menu must have at least one option",
+                ));
+            }
+            if select.max_values as usize > select.options.len() {
+                return Err(ValidationError::Invalid(
+                    "a select menu cannot allow more picks than it has options",
+                ));
+            }
+            if !select.default_values.is_empty() {
+                return Err(ValidationError::Invalid(
+                    "a string select menu picks its defaults through its options",
+                ));
+            }
+        } else if !select.options.is_empty() {
+            return Err(ValidationError::Invalid(
+                "only a string select menu can have options",
+            ));
+        }
+
+        if select.kind != SelectKind::Channel && !select.channel_types.is_empty() {
+            return Err(ValidationError::Invalid(
+                "only a channel select menu can have channel types",
+            ));
+        }
+
+        let defaults = select.default_values.len();
+        if !select.default_values.is_empty()
+            && (defaults < select.min_values as usize || defaults > select.max_values as usize)
+        {
+            return Err(ValidationError::Invalid(
+                "a select menu's number of default values must be between its min and max values",
+            ));
+        }
+
+        let default_values = select
+            .default_values
+            .iter()
+            .map(|default| {
+                let (id, r#type) =
+                    match (select.kind, default) {
+                        (SelectKind::User | SelectKind::Mentionable, SelectDefault::User(id)) => {
+                            (id, "user")
+                        }
+                        (SelectKind::Role | SelectKind::Mentionable, SelectDefault::Role(id)) => {
+                            (id, "role")
+                        }
+                        (SelectKind::Channel, SelectDefault::Channel(id)) => (id, "channel"),
+                        _ => return Err(ValidationError::Invalid(
+                            "a select menu's default values must match the kind of items it offers",
+                        )),
+                    };
+
+                Ok(discord_types::SelectDefaultValue {
+                    id: *id,
+                    r#type: r#type.to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>, ValidationError>>()?;
+
+        let options = select
+            .options
+            .iter()
+            .map(|option| {
+                check_length("select option label", &option.label, 1, 100)?;
+                check_length("select option value", &option.value, 1, 100)?;
+                if let Some(description) = &option.description {
+                    check_length("select option description", description, 0, 100)?;
+                }
+
+                Ok(discord_types::SelectOption {
+                    label: option.label.clone(),
+                    value: option.value.clone(),
+                    description: option.description.clone(),
+                    emoji: option.emoji.clone().map(|e| e.into()),
+                    default: option.selected.then_some(true),
+                })
+            })
+            .collect::<Result<_, ValidationError>>()?;
+
+        let r#type = match select.kind {
+            SelectKind::String => discord_types::ComponentType::StringSelect,
+            SelectKind::User => discord_types::ComponentType::UserSelect,
+            SelectKind::Role => discord_types::ComponentType::RoleSelect,
+            SelectKind::Mentionable => discord_types::ComponentType::MentionableSelect,
+            SelectKind::Channel => discord_types::ComponentType::ChannelSelect,
+        };
+
+        Ok(discord_types::Component {
+            custom_id: Some(select.id.clone()),
+            options: (select.kind == SelectKind::String).then_some(options),
+            placeholder: select.placeholder.clone(),
+            min_values: Some(select.min_values),
+            max_values: Some(select.max_values),
+            disabled: select.disabled.then_some(true),
+            channel_types: (!select.channel_types.is_empty())
+                .then(|| select.channel_types.iter().map(|&t| t.into()).collect()),
+            default_values: (!default_values.is_empty()).then_some(default_values),
+            ..discord_types::Component::new(r#type)
+        })
+    }
+}
+
+impl Resolved {
+    /// Gathers the resolved users, roles and channels whose ids are among `values`.
+    fn from_values(resolved: &discord_types::ResolvedData, values: &[String]) -> Self {
+        let ids: Vec<Snowflake> = values.iter().filter_map(|v| v.parse().ok()).collect();
+
+        Resolved {
+            users: ids
+                .iter()
+                .filter_map(|id| {
+                    let user = resolved.users.get(id)?;
+                    let member = resolved.members.get(id);
+                    Some(User {
+                        nick: member.and_then(|m| m.nick.clone()),
+                        ..user.into()
+                    })
+                })
+                .collect(),
+
+            roles: ids
+                .iter()
+                .filter_map(|id| resolved.roles.get(id))
+                .map(|role| Role {
+                    id: role.id,
+                    name: role.name.clone(),
+                    color: role.color,
+                })
+                .collect(),
+
+            channels: ids
+                .iter()
+                .filter_map(|id| resolved.channels.get(id))
+                .map(|channel| Channel {
+                    id: channel.id,
+                    name: channel.name.clone(),
+                    kind: channel.r#type.into(),
+                    parent_id: channel.parent_id,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<u8> for ChannelType {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => ChannelType::Text,
+            1 => ChannelType::Dm,
+            2 => ChannelType::Voice,
+            3 => ChannelType::GroupDm,
+            4 => ChannelType::Category,
+            5 => ChannelType::Announcement,
+            10 => ChannelType::AnnouncementThread,
+            11 => ChannelType::PublicThread,
+            12 => ChannelType::PrivateThread,
+            13 => ChannelType::StageVoice,
+            14 => ChannelType::Directory,
+            15 => ChannelType::Forum,
+            16 => ChannelType::Media,
+            other => ChannelType::Unknown(other),
+        }
+    }
+}
+
+impl From<ChannelType> for u8 {
+    fn from(channel_type: ChannelType) -> Self {
+        match channel_type {
+            ChannelType::Text => 0,
+            ChannelType::Dm => 1,
+            ChannelType::Voice => 2,
+            ChannelType::GroupDm => 3,
+            ChannelType::Category => 4,
+            ChannelType::Announcement => 5,
+            ChannelType::AnnouncementThread => 10,
+            ChannelType::PublicThread => 11,
+            ChannelType::PrivateThread => 12,
+            ChannelType::StageVoice => 13,
+            ChannelType::Directory => 14,
+            ChannelType::Forum => 15,
+            ChannelType::Media => 16,
+            ChannelType::Unknown(other) => other,
+        }
+    }
+}
+
+impl From<ButtonStyle> for discord_types::ButtonStyle {
+    fn from(style: ButtonStyle) -> Self {
+        match style {
+            ButtonStyle::Primary => discord_types::ButtonStyle::Primary,
+            ButtonStyle::Secondary => discord_types::ButtonStyle::Secondary,
+            ButtonStyle::Success => discord_types::ButtonStyle::Success,
+            ButtonStyle::Danger => discord_types::ButtonStyle::Danger,
+            ButtonStyle::Link => discord_types::ButtonStyle::Link,
+            ButtonStyle::Premium => discord_types::ButtonStyle::Premium,
+        }
+    }
+}
+
+impl From<Emoji> for discord_types::Emoji {
+    fn from(emoji: Emoji) -> Self {
+        discord_types::Emoji {
+            id: emoji.id,
+            name: emoji.name,
+            animated: emoji.animated.then_some(true),
+        }
+    }
+}
+
+impl From<&discord_types::Emoji> for Emoji {
+    fn from(emoji: &discord_types::Emoji) -> Self {
+        Emoji {
+            id: emoji.id,
+            name: emoji.name.clone(),
+            animated: emoji.animated.unwrap_or(false),
+        }
+    }
+}
+
+impl TryFrom<Vec<Choice>> for discord_types::InteractionResponse {
+    type Error = ValidationError;
+
+    fn try_from(choices: Vec<Choice>) -> Result<Self, Self::Error> {
+        check_count("autocomplete choices", choices.len(), 25)?;
+
+        let choices = choices
+            .into_iter()
+            .map(|choice| {
+                check_length("choice name", &choice.name, 1, 100)?;
+                let value = match choice.value {
+                    ChoiceValue::String(s) => {
+                        check_length("choice value", &s, 1, 100)?;
+                        serde_json::Value::from(s)
+                    }
+                    ChoiceValue::Integer(n) => serde_json::Value::from(n),
+                    ChoiceValue::Number(n) => serde_json::Value::from(n),
+                };
+
+                for name in choice.name_localizations.values() {
+                    check_length("localized choice name", name, 1, 100)?;
+                }
+
+                Ok(discord_types::CommandOptionChoice {
+                    name: choice.name,
+                    name_localizations: if choice.name_localizations.is_empty() {
+                        None
+                    } else {
+                        Some(choice.name_localizations)
+                    },
+                    value,
+                })
+            })
+            .collect::<Result<Vec<_>, ValidationError>>()?;
+
+        Ok(discord_types::InteractionResponse {
+            r#type: discord_types::InteractionCallbackType::ApplicationCommandAutocompleteResult,
+            data: Some(discord_types::InteractionCallbackData {
+                choices: Some(choices),
+                ..Default::default()
+            }),
+            followups: Vec::new(),
+            raw: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn source_message(value: serde_json::Value) -> SourceMessage {
+        let msg: discord_types::Message = serde_json::from_value(value).unwrap();
+        SourceMessage::from(&msg)
+    }
+
+    fn message(flags: u32, components: serde_json::Value) -> serde_json::Value {
+        json!({
+            "id": "3",
+            "channel_id": "4",
+            "author": { "id": "5" },
+            "timestamp": "2024-01-01T00:00:00Z",
+            "edited_timestamp": null,
+            "content": "hello",
+            "flags": flags,
+            "components": components,
+        })
+    }
+
+    fn request(value: serde_json::Value) -> discord_types::InteractionRequest {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn component_interactions_from_dms_use_the_plain_user() {
+        let req = request(json!({
+            "id": "1",
+            "application_id": "2",
+            "token": "t",
+            "type": 3,
+            "data": { "custom_id": "b", "component_type": 2 },
+            "user": { "id": "7" },
+            "message": message(0, json!([])),
+        }));
+
+        let mc = MessageComponent::try_from(&req).unwrap();
+
+        assert_eq!(mc.user_id, Snowflake::new(7));
+    }
+
+    #[test]
+    fn interactions_without_a_user_name_the_missing_field() {
+        let req = request(json!({
+            "id": "1",
+            "application_id": "2",
+            "token": "t",
+            "type": 2,
+            "data": { "name": "hello" },
+        }));
+
+        assert_eq!(
+            ApplicationCommand::try_from(&req).err(),
+            Some(ConversionError::MissingField("member.user"))
+        );
+    }
+
+    #[test]
+    fn modal_submits_gather_values_from_every_component_of_every_row() {
+        let req = request(json!({
+            "id": "1",
+            "application_id": "2",
+            "token": "t",
+            "type": 5,
+            "data": {
+                "custom_id": "form",
+                "components": [
+                    { "type": 1, "components": [] },
+                    {
+                        "type": 1,
+                        "components": [
+                            { "type": 4, "custom_id": "a", "value": "first" },
+                            { "type": 4, "custom_id": "b", "value": "second" },
+                        ],
+                    },
+                    { "type": 1, "components": [{ "type": 4, "custom_id": "c" }] },
+                ],
+            },
+        }));
+
+        let ms = ModalSubmit::try_from(&req).unwrap();
+
+        assert_eq!(ms.values.len(), 3);
+        assert_eq!(ms.values["a"], "first");
+        assert_eq!(ms.values["b"], "second");
+        assert_eq!(ms.values["c"], "");
+        assert_eq!(ms.origin, ModalOrigin::ApplicationCommand);
+        assert!(ms.source.is_none());
+    }
+
+    #[test]
+    fn edits_of_a_source_message_only_keep_flags_that_a_response_can_set() {
+        let has_thread = 1 << 5;
+        let flags = has_thread | MessageFlags::SUPPRESS_EMBEDS.bits();
+        let source = source_message(message(flags, json!([])));
+
+        let edit = Message::from_source(&source);
+
+        assert_eq!(edit.flags, MessageFlags::SUPPRESS_EMBEDS);
+    }
+
+    #[test]
+    fn buttons_with_unknown_styles_are_kept_as_raw_json() {
+        let button = json!({ "type": 2, "style": 9, "custom_id": "b", "label": "B" });
+        let other = json!({ "type": 99, "custom_id": "q" });
+        let source = source_message(message(
+            0,
+            json!([{ "type": 1, "components": [button, other] }]),
+        ));
+
+        assert_eq!(
+            source.rows[0].components,
+            [RowComponent::Unknown(button), RowComponent::Unknown(other)]
+        );
+    }
+
+    #[test]
+    fn edits_of_a_source_message_keep_unknown_components() {
+        let unknown = json!({ "type": 99, "custom_id": "q", "fancy": true });
+        let source = source_message(message(0, json!([{ "type": 1, "components": [unknown] }])));
+
+        let data = discord_types::InteractionCallbackData::try_from(Message::from_source(&source))
+            .unwrap();
+
+        assert_eq!(
+            serde_json::to_value(data.components).unwrap(),
+            json!([{ "type": 1, "components": [unknown] }])
+        );
+    }
+}
@@ -0,0 +1,364 @@
+/*!
+ * Keeps slow handlers within Discord's 3 second response window. The handler runs on a blocking
+ * thread while the runtime waits for it against a time budget. If the budget runs out, the runtime
+ * acknowledges the interaction with a deferred response through the callback endpoint, waits for
+ * the handler to finish, and then delivers its response through the interaction webhook.
+ */
+
+use super::discord_types::{
+    InteractionCallbackType, InteractionRequest, InteractionResponse, InteractionType,
+};
+use super::handler::{handle_interaction, HandleError, InteractionHandler};
+use super::user_types::{InteractionToken, MessageFlags, ValidationError};
+use super::webhook::{HttpTransport, WebhookClient, WebhookError};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The result of handling an interaction under a deadline.
+pub enum Outcome {
+    /// The handler finished in time, and its response should be sent as usual.
+    Respond(Box<InteractionResponse>),
+    /// The handler was too slow, so the interaction was deferred and completed via the webhook.
+    Delivered,
+}
+
+impl Outcome {
+    /// Wraps a response that should be sent as usual.
+    pub fn respond(res: InteractionResponse) -> Self {
+        Outcome::Respond(Box::new(res))
+    }
+}
+
+/// Handles an interaction, deferring it if the handler takes longer than `deadline`.
+pub async fn handle_with_deadline<T>(
+    req: InteractionRequest,
+    deadline: Duration,
+    transport: Arc<dyn HttpTransport>,
+) -> Result<Outcome, HandleError>
+where
+    T: InteractionHandler + 'static,
+{
+    let deferral = match deferral_type(&req) {
+        Some(deferral) => deferral,
+        None => return handle_interaction::<T>(&req).map(Outcome::respond),
+    };
+
+    let client = WebhookClient::with_transport(InteractionToken::from(&req), transport);
+    let mut task = tokio::task::spawn_blocking(move || handle_interaction::<T>(&req));
+
+    if let Ok(joined) = tokio::time::timeout(deadline, &mut task).await {
+        return unwind(joined).map(Outcome::respond);
+    }
+
+    tracing::warn!({ ?deadline }, "Handler exceeded its deadline, deferring response");
+
+    let deferred = InteractionResponse {
+        r#type: deferral,
+        data: None,
+        followups: Vec::new(),
+        raw: None,
+    };
+    if let Err(err) = client.create_response(deferred).await {
+        tracing::error!({ %err }, "Could not defer response");
+    }
+
+    // The loading message must be cleared even if the handler failed or panicked, since nothing
+    // else will replace it.
+    let res = match task.await {
+        Ok(Ok(res)) => res,
+        Ok(Err(err)) => {
+            clear_deferral(&client, deferral).await;
+            return Err(err);
+        }
+        Err(err) => {
+            clear_deferral(&client, deferral).await;
+            std::panic::resume_unwind(err.into_panic());
+        }
+    };
+    if let Err(err) = deliver(&client, deferral, res).await {
+        tracing::error!({ %err }, "Could not deliver deferred response");
+    }
+
+    Ok(Outcome::Delivered)
+}
+
+/// Picks how to defer an interaction, or returns `None` if it must be answered directly.
+fn deferral_type(req: &InteractionRequest) -> Option<InteractionCallbackType> {
+    match req.r#type {
+        InteractionType::Ping
+        | InteractionType::ApplicationCommandAutocomplete
+        | InteractionType::Unknown(_) => None,
+        InteractionType::ApplicationCommand => {
+            Some(InteractionCallbackType::DeferredChannelMessageWithSource)
+        }
+        InteractionType::MessageComponent | InteractionType::ModalSubmit => {
+            if req.message.is_some() {
+                Some(InteractionCallbackType::DeferredUpdateMessage)
+            } else {
+                Some(InteractionCallbackType::DeferredChannelMessageWithSource)
+            }
+        }
+    }
+}
+
+/// Delivers the handler's late response through the webhook, followed by its follow-ups.
+async fn deliver<T: HttpTransport>(
+    client: &WebhookClient<T>,
+    deferral: InteractionCallbackType,
+    mut res: InteractionResponse,
+) -> Result<(), WebhookError> {
+    let followups = std::mem::take(&mut res.followups);
+    if let Err(err) = deliver_response(client, deferral, res).await {
+        clear_deferral(client, deferral).await;
+        return Err(err);
+    }
+    for data in followups {
+        client.follow_up_data(data).await?;
+    }
+    Ok(())
+}
+
+/// Delivers the handler's late response through the webhook, in whichever way matches the
+/// deferral that was already sent.
+async fn deliver_response<T: HttpTransport>(
+    client: &WebhookClient<T>,
+    deferral: InteractionCallbackType,
+    res: InteractionResponse,
+) -> Result<(), WebhookError> {
+    let unsupported = |reason| Err(WebhookError::Invalid(ValidationError::Unsupported(reason)));
+
+    if res.raw.is_some() {
+        return unsupported("a raw response cannot be sent once the response has been deferred");
+    }
+
+    let data = match res.r#type {
+        InteractionCallbackType::ChannelMessageWithSource
+        | InteractionCallbackType::UpdateMessage => res.data.unwrap_or_default(),
+
+        // The handler deferred the response itself, and will complete it through the webhook.
+        InteractionCallbackType::DeferredChannelMessageWithSource
+        | InteractionCallbackType::DeferredUpdateMessage => return Ok(()),
+
+        InteractionCallbackType::Modal => {
+            return unsupported("a modal cannot be opened once the response has been deferred")
+        }
+
+        _ => return unsupported("the response cannot be sent once the response has been deferred"),
+    };
+
+    let ephemeral =
+        MessageFlags::from_bits(data.flags.unwrap_or(0)).contains(MessageFlags::EPHEMERAL);
+
+    match (deferral, res.r#type) {
+        // The deferral left the source message untouched, so an edit applies to it directly,
+        // while a new message becomes a follow-up.
+        (
+            InteractionCallbackType::DeferredUpdateMessage,
+            InteractionCallbackType::UpdateMessage,
+        ) => client.edit_original_data(data).await,
+        (InteractionCallbackType::DeferredUpdateMessage, _) => {
+            client.follow_up_data(data).await.map(|_| ())
+        }
+
+        // The deferral showed a public loading message, which can't be made ephemeral afterwards,
+        // so an ephemeral response replaces it with an ephemeral follow-up.
+        (_, _) if ephemeral => {
+            client.delete_original().await?;
+            client.follow_up_data(data).await.map(|_| ())
+        }
+        (_, _) => client.edit_original_data(data).await,
+    }
+}
+
+/// Removes the loading message shown by a deferral, once it's clear that the handler's response
+/// won't replace it. Without this, the message would keep loading until the interaction token
+/// expires. A deferred update shows no loading state, so it needs no clearing.
+async fn clear_deferral<T: HttpTransport>(
+    client: &WebhookClient<T>,
+    deferral: InteractionCallbackType,
+) {
+    if deferral != InteractionCallbackType::DeferredChannelMessageWithSource {
+        return;
+    }
+
+    if let Err(err) = client.delete_original().await {
+        tracing::error!({ %err }, "Could not clear deferred response");
+    }
+}
+
+/// Unwraps the result of the handler's task, resuming any panic that occurred within it.
+fn unwind<R>(joined: Result<R, tokio::task::JoinError>) -> R {
+    match joined {
+        Ok(r) => r,
+        Err(err) => std::panic::resume_unwind(err.into_panic()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user_types::{ApplicationCommand, Message, Response};
+    use crate::webhook::mock::{block_on, interaction_id, MockTransport};
+    use crate::webhook::{HttpRequest, HttpResponse};
+    use lambda_http::http::Method;
+    use std::panic::AssertUnwindSafe;
+
+    const DEADLINE: Duration = Duration::from_millis(20);
+
+    /// Sleeps for long enough that the handler misses its deadline.
+    fn miss_deadline() {
+        std::thread::sleep(DEADLINE * 10);
+    }
+
+    struct SlowMessage;
+
+    impl InteractionHandler for SlowMessage {
+        fn handle_application_command(_: ApplicationCommand) -> Response {
+            miss_deadline();
+            Response::Message(Message::new().text("late"))
+        }
+    }
+
+    struct SlowEphemeralMessage;
+
+    impl InteractionHandler for SlowEphemeralMessage {
+        fn handle_application_command(_: ApplicationCommand) -> Response {
+            miss_deadline();
+            Response::Message(Message::new().text("late").ephemeral())
+        }
+    }
+
+    struct SlowPanic;
+
+    impl InteractionHandler for SlowPanic {
+        fn handle_application_command(_: ApplicationCommand) -> Response {
+            miss_deadline();
+            panic!("handler failed");
+        }
+    }
+
+    fn application_command() -> InteractionRequest {
+        serde_json::from_value(serde_json::json!({
+            "id": interaction_id(0).to_string(),
+            "application_id": "42",
+            "token": "secret",
+            "type": 2,
+            "data": { "name": "slow" },
+            "member": { "user": { "id": "7" } },
+        }))
+        .unwrap()
+    }
+
+    fn ok(_: &HttpRequest) -> HttpResponse {
+        HttpResponse {
+            status: 200,
+            body: br#"{"id": "1"}"#.to_vec(),
+        }
+    }
+
+    /// Summarizes requests by their method and the part of their URL after the token.
+    fn summarize(requests: &[HttpRequest]) -> Vec<(Method, &str)> {
+        requests
+            .iter()
+            .map(|r| (r.method.clone(), r.url.split("secret").nth(1).unwrap_or("")))
+            .collect()
+    }
+
+    #[test]
+    fn late_messages_replace_the_deferred_loading_message() {
+        let transport = MockTransport::with_responder(ok);
+
+        let outcome = block_on(handle_with_deadline::<SlowMessage>(
+            application_command(),
+            DEADLINE,
+            transport.clone(),
+        ));
+
+        assert!(matches!(outcome, Ok(Outcome::Delivered)));
+        let requests = transport.requests();
+        assert_eq!(
+            summarize(&requests),
+            [
+                (Method::POST, "/callback"),
+                (Method::PATCH, "/messages/@original"),
+            ]
+        );
+        let deferral: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(deferral["type"], 5);
+        let edit: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+        assert_eq!(edit["content"], "late");
+    }
+
+    #[test]
+    fn late_ephemeral_messages_replace_the_loading_message_with_a_follow_up() {
+        let transport = MockTransport::with_responder(ok);
+
+        let outcome = block_on(handle_with_deadline::<SlowEphemeralMessage>(
+            application_command(),
+            DEADLINE,
+            transport.clone(),
+        ));
+
+        assert!(matches!(outcome, Ok(Outcome::Delivered)));
+        let requests = transport.requests();
+        assert_eq!(
+            summarize(&requests),
+            [
+                (Method::POST, "/callback"),
+                (Method::DELETE, "/messages/@original"),
+                (Method::POST, ""),
+            ]
+        );
+        let follow_up: serde_json::Value = serde_json::from_slice(&requests[2].body).unwrap();
+        assert_eq!(follow_up["flags"], MessageFlags::EPHEMERAL.bits());
+    }
+
+    #[test]
+    fn failed_deliveries_clear_the_loading_message() {
+        let transport = MockTransport::with_responder(|request| match request.method {
+            Method::PATCH => HttpResponse {
+                status: 500,
+                body: Vec::new(),
+            },
+            _ => ok(request),
+        });
+
+        let outcome = block_on(handle_with_deadline::<SlowMessage>(
+            application_command(),
+            DEADLINE,
+            transport.clone(),
+        ));
+
+        assert!(matches!(outcome, Ok(Outcome::Delivered)));
+        assert_eq!(
+            summarize(&transport.requests()),
+            [
+                (Method::POST, "/callback"),
+                (Method::PATCH, "/messages/@original"),
+                (Method::DELETE, "/messages/@original"),
+            ]
+        );
+    }
+
+    #[test]
+    fn late_panics_clear_the_loading_message_before_resuming() {
+        let transport = MockTransport::with_responder(ok);
+
+        let panicked = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            block_on(handle_with_deadline::<SlowPanic>(
+                application_command(),
+                DEADLINE,
+                transport.clone(),
+            ))
+        }));
+
+        assert!(panicked.is_err());
+        assert_eq!(
+            summarize(&transport.requests()),
+            [
+                (Method::POST, "/callback"),
+                (Method::DELETE, "/messages/@original"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,472 @@
+/*!
+ * A client for the interaction webhook, which lets the application keep talking to the user after
+ * the initial response has been sent: completing deferred responses, sending follow-up messages,
+ * and editing or deleting what was already sent. HTTP requests go through the `HttpTransport`
+ * trait, so the client can be pointed at a mock transport or a local server in tests.
+ */
+
+use super::discord_types;
+use super::multipart;
+use super::snowflake::Snowflake;
+use super::user_types::{InteractionToken, Message, MessageFlags, ValidationError};
+use lambda_http::http::Method;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// The base URL of the Discord REST API.
+pub(crate) const API_BASE_URL: &str = "https://discord.com/api/v10";
+
+/// A boxed future, as returned by an `HttpTransport`.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// An HTTP request made by the webhook client.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: String,
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+}
+
+/// The status and body of an HTTP response received by the webhook client.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+/// The means by which the webhook client sends its HTTP requests. The default transport uses
+/// `reqwest`, but any other HTTP client, or a mock for testing, can be plugged in instead.
+pub trait HttpTransport: Send + Sync {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, WebhookError>>;
+}
+
+/// The default transport, which sends requests using a `reqwest` client.
+#[derive(Clone, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+/// An error raised when a webhook request could not be completed.
+#[derive(Debug, PartialEq)]
+pub enum WebhookError {
+    /// The interaction token is more than 15 minutes old, so Discord would reject it.
+    TokenExpired,
+    /// The request could not be sent, or no response was received.
+    Transport(String),
+    /// Discord responded with an unsuccessful status code.
+    Status { status: u16, body: String },
+    /// Discord's response could not be understood.
+    Decode(String),
+    /// The message breaks one of Discord's limits, so it was not sent.
+    Invalid(ValidationError),
+}
+
+/// A client for the webhook of a single interaction.
+pub struct WebhookClient<T: HttpTransport = ReqwestTransport> {
+    token: InteractionToken,
+    transport: T,
+    base_url: String,
+}
+
+impl ReqwestTransport {
+    /// Creates a transport using an existing `reqwest` client.
+    pub fn new(client: reqwest::Client) -> Self {
+        ReqwestTransport { client }
+    }
+}
+
+impl HttpTransport for ReqwestTransport {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, WebhookError>> {
+        Box::pin(async move {
+            let mut builder = self
+                .client
+                .request(request.method, &request.url)
+                .body(request.body);
+
+            if let Some(content_type) = request.content_type {
+                builder = builder.header("Content-Type", content_type);
+            }
+
+            let res = builder
+                .send()
+                .await
+                .map_err(|e| WebhookError::Transport(e.to_string()))?;
+
+            let status = res.status().as_u16();
+            let body = res
+                .bytes()
+                .await
+                .map_err(|e| WebhookError::Transport(e.to_string()))?;
+
+            Ok(HttpResponse {
+                status,
+                body: body.to_vec(),
+            })
+        })
+    }
+}
+
+impl<T: HttpTransport + ?Sized> HttpTransport for Arc<T> {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, WebhookError>> {
+        (**self).send(request)
+    }
+}
+
+impl fmt::Display for WebhookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WebhookError::TokenExpired => write!(f, "the interaction token has expired"),
+            WebhookError::Transport(err) => write!(f, "request failed: {err}"),
+            WebhookError::Status { status, body } => {
+                write!(f, "discord responded with status {status}: {body}")
+            }
+            WebhookError::Decode(err) => write!(f, "could not decode response: {err}"),
+            WebhookError::Invalid(err) => write!(f, "invalid message: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for WebhookError {}
+
+impl From<ValidationError> for WebhookError {
+    fn from(err: ValidationError) -> Self {
+        WebhookError::Invalid(err)
+    }
+}
+
+impl WebhookClient {
+    /// Creates a client for the given interaction, using the default transport.
+    pub fn new(token: InteractionToken) -> Self {
+        Self::with_transport(token, ReqwestTransport::default())
+    }
+}
+
+impl<T: HttpTransport> WebhookClient<T> {
+    /// Creates a client for the given interaction, sending requests through `transport`.
+    pub fn with_transport(token: InteractionToken, transport: T) -> Self {
+        WebhookClient {
+            token,
+            transport,
+            base_url: API_BASE_URL.to_string(),
+        }
+    }
+
+    /// Sets the base URL of the API, such as a local mock server. Defaults to Discord's API.
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Sends a follow-up message to the interaction, returning the id of the new message.
+    pub async fn follow_up(&self, msg: Message) -> Result<Snowflake, WebhookError> {
+        self.follow_up_data(msg.try_into()?).await
+    }
+
+    /// Replaces the original response to the interaction. This also completes a deferred
+    /// response.
+    pub async fn edit_original(&self, msg: Message) -> Result<(), WebhookError> {
+        self.edit_original_data(msg.try_into()?).await
+    }
+
+    /// Deletes the original response to the interaction.
+    pub async fn delete_original(&self) -> Result<(), WebhookError> {
+        self.send(Method::DELETE, "/messages/@original", None)
+            .await?;
+        Ok(())
+    }
+
+    /// Replaces a follow-up message that was previously sent to the interaction.
+    pub async fn edit_follow_up(
+        &self,
+        message_id: Snowflake,
+        msg: Message,
+    ) -> Result<(), WebhookError> {
+        let mut data = discord_types::InteractionCallbackData::try_from(msg)?;
+        data.flags = strip_ephemeral(data.flags);
+        let path = format!("/messages/{message_id}");
+        self.send(Method::PATCH, &path, Some(encode(&data, &data.files)))
+            .await?;
+        Ok(())
+    }
+
+    /// Deletes a follow-up message that was previously sent to the interaction.
+    pub async fn delete_follow_up(&self, message_id: Snowflake) -> Result<(), WebhookError> {
+        let path = format!("/messages/{message_id}");
+        self.send(Method::DELETE, &path, None).await?;
+        Ok(())
+    }
+
+    /// Sends the initial response to the interaction through the callback endpoint, rather than as
+    /// the body of the HTTP response to Discord's request.
+    pub(crate) async fn create_response(
+        &self,
+        res: discord_types::InteractionResponse,
+    ) -> Result<(), WebhookError> {
+        let url = format!(
+            "{}/interactions/{}/{}/callback",
+            self.base_url, self.token.interaction_id, self.token.token
+        );
+        self.send_to(Method::POST, url, Some(encode(&res.to_json(), res.files())))
+            .await?;
+        Ok(())
+    }
+
+    /// Sends the initial response through the callback endpoint, followed by its follow-ups.
+    pub(crate) async fn respond(
+        &self,
+        mut res: discord_types::InteractionResponse,
+    ) -> Result<(), WebhookError> {
+        let followups = std::mem::take(&mut res.followups);
+        self.create_response(res).await?;
+        for data in followups {
+            self.follow_up_data(data).await?;
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn follow_up_data(
+        &self,
+        data: discord_types::InteractionCallbackData,
+    ) -> Result<Snowflake, WebhookError> {
+        let res = self
+            .send(Method::POST, "", Some(encode(&data, &data.files)))
+            .await?;
+
+        let created: serde_json::Value =
+            serde_json::from_slice(&res.body).map_err(|e| WebhookError::Decode(e.to_string()))?;
+
+        created["id"]
+            .as_str()
+            .and_then(|id| id.parse().ok())
+            .ok_or(WebhookError::Decode("missing message id".to_string()))
+    }
+
+    pub(crate) async fn edit_original_data(
+        &self,
+        mut data: discord_types::InteractionCallbackData,
+    ) -> Result<(), WebhookError> {
+        data.flags = strip_ephemeral(data.flags);
+        self.send(
+            Method::PATCH,
+            "/messages/@original",
+            Some(encode(&data, &data.files)),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<(String, Vec<u8>)>,
+    ) -> Result<HttpResponse, WebhookError> {
+        let url = self.token.webhook_url(&self.base_url, path);
+        self.send_to(method, url, body).await
+    }
+
+    async fn send_to(
+        &self,
+        method: Method,
+        url: String,
+        body: Option<(String, Vec<u8>)>,
+    ) -> Result<HttpResponse, WebhookError> {
+        if self.token.is_expired() {
+            return Err(WebhookError::TokenExpired);
+        }
+
+        let (content_type, body) = match body {
+            Some((content_type, body)) => (Some(content_type), body),
+            None => (None, Vec::new()),
+        };
+        let request = HttpRequest {
+            method,
+            url,
+            content_type,
+            body,
+        };
+
+        let res = self.transport.send(request).await?;
+
+        if !(200..300).contains(&res.status) {
+            return Err(WebhookError::Status {
+                status: res.status,
+                body: String::from_utf8_lossy(&res.body).into_owned(),
+            });
+        }
+
+        Ok(res)
+    }
+}
+
+/// Clears the ephemeral flag from a message's raw flags, since it can only be set when a message is
+/// first sent, while keeping any other flags.
+pub(crate) fn strip_ephemeral(flags: Option<u32>) -> Option<u32> {
+    let mut flags = MessageFlags::from_bits(flags.unwrap_or(0));
+    flags.remove(MessageFlags::EPHEMERAL);
+    (!flags.is_empty()).then_some(flags.bits())
+}
+
+/// Encodes a request body, as multipart form data if it has files to upload, and as JSON otherwise.
+fn encode(
+    payload: &impl serde::Serialize,
+    files: &[discord_types::FileUpload],
+) -> (String, Vec<u8>) {
+    multipart::encode(&serde_json::json!(payload), files)
+}
+
+/// Test helpers for driving the webhook client, and the runtime built on it, without a network.
+#[cfg(test)]
+pub(crate) mod mock {
+    use super::*;
+    use std::sync::Mutex;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    /// A transport that records its requests, and answers each of them with the response picked by
+    /// its responder.
+    pub(crate) struct MockTransport {
+        requests: Mutex<Vec<HttpRequest>>,
+        responder: Box<dyn Fn(&HttpRequest) -> HttpResponse + Send + Sync>,
+    }
+
+    impl MockTransport {
+        /// Creates a transport that answers every request with the same response.
+        pub(crate) fn new(status: u16, body: &str) -> Arc<Self> {
+            let body = body.as_bytes().to_vec();
+            Self::with_responder(move |_| HttpResponse {
+                status,
+                body: body.clone(),
+            })
+        }
+
+        /// Creates a transport that answers each request with the response `responder` picks.
+        pub(crate) fn with_responder(
+            responder: impl Fn(&HttpRequest) -> HttpResponse + Send + Sync + 'static,
+        ) -> Arc<Self> {
+            Arc::new(MockTransport {
+                requests: Mutex::new(Vec::new()),
+                responder: Box::new(responder),
+            })
+        }
+
+        /// Returns the requests sent so far, oldest first.
+        pub(crate) fn requests(&self) -> Vec<HttpRequest> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    impl HttpTransport for MockTransport {
+        fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, WebhookError>> {
+            let response = (self.responder)(&request);
+            self.requests.lock().unwrap().push(request);
+            Box::pin(async move { Ok(response) })
+        }
+    }
+
+    /// Creates the id of an interaction created `age_ms` milliseconds ago.
+    pub(crate) fn interaction_id(age_ms: u64) -> Snowflake {
+        const DISCORD_EPOCH_MS: u64 = 1_420_070_400_000;
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+
+        Snowflake::new((now_ms - age_ms - DISCORD_EPOCH_MS) << 22)
+    }
+
+    /// Runs a future to completion on a fresh single-threaded runtime.
+    pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mock::{block_on, interaction_id, MockTransport};
+    use super::*;
+
+    /// Creates a token for an interaction created `age_ms` milliseconds ago.
+    fn token(age_ms: u64) -> InteractionToken {
+        InteractionToken {
+            interaction_id: interaction_id(age_ms),
+            application_id: Snowflake::new(42),
+            token: "secret".to_string(),
+        }
+    }
+
+    #[test]
+    fn follow_up_posts_to_the_webhook_and_returns_the_message_id() {
+        let transport = MockTransport::new(200, r#"{"id": "123"}"#);
+        let client =
+            WebhookClient::with_transport(token(0), transport.clone()).base_url("http://mock/api/");
+
+        let id = block_on(client.follow_up(Message::new().text("hello")));
+
+        assert_eq!(id, Ok(Snowflake::new(123)));
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, Method::POST);
+        assert_eq!(requests[0].url, "http://mock/api/webhooks/42/secret");
+        assert_eq!(
+            requests[0].content_type.as_deref(),
+            Some("application/json")
+        );
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body["content"], "hello");
+    }
+
+    #[test]
+    fn edit_original_patches_the_original_response_without_the_ephemeral_flag() {
+        let transport = MockTransport::new(200, "{}");
+        let client =
+            WebhookClient::with_transport(token(0), transport.clone()).base_url("http://mock");
+
+        let edited = block_on(client.edit_original(Message::new().text("edited").ephemeral()));
+
+        assert_eq!(edited, Ok(()));
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, Method::PATCH);
+        assert_eq!(
+            requests[0].url,
+            "http://mock/webhooks/42/secret/messages/@original"
+        );
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body["content"], "edited");
+        assert!(body.get("flags").is_none());
+    }
+
+    #[test]
+    fn unsuccessful_statuses_are_reported() {
+        let transport = MockTransport::new(404, "unknown webhook");
+        let client = WebhookClient::with_transport(token(0), transport.clone());
+
+        let deleted = block_on(client.delete_original());
+
+        assert_eq!(
+            deleted,
+            Err(WebhookError::Status {
+                status: 404,
+                body: "unknown webhook".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn expired_tokens_are_rejected_without_sending_anything() {
+        let transport = MockTransport::new(200, r#"{"id": "123"}"#);
+        let client = WebhookClient::with_transport(token(16 * 60 * 1000), transport.clone());
+
+        let id = block_on(client.follow_up(Message::new().text("too late")));
+
+        assert_eq!(id, Err(WebhookError::TokenExpired));
+        assert!(transport.requests().is_empty());
+    }
+}